@@ -1,17 +1,33 @@
+use std::marker::PhantomData;
+
 use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
     prelude::*,
     render::render_resource::{AsBindGroup, ShaderRef},
+    utils::BoxedFuture,
 };
 use bevy_asset_loader::asset_collection::AssetCollection;
+use serde::de::DeserializeOwned;
 
-pub const ICON_INDEX_SCROLL_MARKER: usize = 56;
+use crate::{enemy::EnemyDefinitions, items::definitions::ItemDefinitions};
 
-#[derive(Resource)]
+pub const ICON_INDEX_SCROLL_MARKER: usize = 56;
+pub const ICON_INDEX_POISON: usize = 40;
+pub const ICON_INDEX_BLEED: usize = 41;
+pub const ICON_INDEX_STUN: usize = 42;
+pub const ICON_INDEX_BURN: usize = 43;
+pub const ICON_INDEX_INTENT_ATTACK: usize = 19;
+pub const ICON_INDEX_INTENT_BUFF: usize = 20;
+pub const ICON_INDEX_INTENT_DEFEND: usize = 21;
+pub const ICON_INDEX_INTENT_DEBUFF: usize = 22;
+pub const ICON_INDEX_INTENT_UNKNOWN: usize = 44;
+
+#[derive(Resource, Default)]
 pub struct GameMaterials {
     pub text_bg: Handle<TextUIMaterial>,
 }
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct GameSprites {
     #[asset(path = "sprites/background.png")]
     pub background: Handle<Image>,
@@ -49,6 +65,12 @@ pub struct GameSprites {
     #[asset(path = "sprites/start_battle_button_hover.png")]
     pub start_battle_button_hover: Handle<Image>,
 
+    #[asset(path = "sprites/suggest_button.png")]
+    pub suggest_button: Handle<Image>,
+
+    #[asset(path = "sprites/suggest_button_hover.png")]
+    pub suggest_button_hover: Handle<Image>,
+
     #[asset(path = "sprites/start_game_button.png")]
     pub start_game_button: Handle<Image>,
 
@@ -66,26 +88,77 @@ pub struct GameSprites {
 
     #[asset(path = "sprites/pirate.png")]
     pub pirate_sheet: Handle<Image>,
-
-    #[asset(texture_atlas_layout(tile_size_x = 48., tile_size_y = 32., columns = 3, rows = 1))]
-    pub skeleton_layout: Handle<TextureAtlasLayout>,
-
-    #[asset(path = "sprites/skeleton_pirate.png")]
-    pub skeleton_sheet: Handle<Image>,
 }
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct GameFonts {
     #[asset(path = "fonts/minimal5x7.ttf")]
     pub font: Handle<Font>,
 }
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct GameAudio {
     #[asset(path = "audio/queen-annex27s-revenge-pirate-shanty-piano-solo-blackbeard-194624.mp3")]
     pub music: Handle<AudioSource>,
 }
 
+#[derive(AssetCollection, Resource)]
+pub struct GameData {
+    #[asset(path = "data/base.items.ron")]
+    pub items: Handle<ItemDefinitions>,
+
+    #[asset(path = "data/base.enemies.ron")]
+    pub enemies: Handle<EnemyDefinitions>,
+}
+
+/// Reads a RON data file straight from disk, for tools running without an
+/// `AssetServer`.
+pub fn load_ron<T: DeserializeOwned>(path: impl AsRef<std::path::Path>) -> Result<T, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ron::from_str(&text).map_err(|e| e.to_string())
+}
+
+/// Loads any deserializable asset from a RON file with one of `extensions`.
+pub struct RonAssetLoader<A> {
+    extensions: &'static [&'static str],
+    _asset: PhantomData<A>,
+}
+
+impl<A> RonAssetLoader<A> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            _asset: PhantomData,
+        }
+    }
+}
+
+impl<A> AssetLoader for RonAssetLoader<A>
+where
+    A: Asset + DeserializeOwned,
+{
+    type Asset = A;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<A, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
+
 #[derive(AsBindGroup, Asset, TypePath, Debug, Clone)]
 pub struct TextUIMaterial {
     #[uniform(0)]
@@ -0,0 +1,366 @@
+use rand::{seq::SliceRandom, RngCore};
+use serde::Serialize;
+
+use crate::{
+    battle::sim::{BattleOutcome, BattleSim, SimEnemy, SimPlayer},
+    common::Hp,
+    enemy::{loot_bonus, EnemyDefinitions},
+    inventory::{roll_loot, LootBonus, INVENTORY_SCROLL_SIZE},
+    items::{
+        abilities::ModifierScope, definitions::ItemDefinitions, ItemComponents, ItemType, Rarity,
+    },
+    player::STARTING_PLAYER_HP,
+    rng::Rng,
+    solver::{solve_arrangement, SolverGoal},
+    BattleWins,
+};
+
+/// Battles still undecided after this many rounds count as a stalemate.
+pub const MAX_BATTLE_ROUNDS: usize = 200;
+
+/// How the bot fills and orders the inventory scroll between battles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum BotPolicy {
+    /// Keeps a random selection in a random order.
+    Random,
+    /// Keeps the rarest items, with support items ahead of the ones that
+    /// deal damage so buffs and ammo land before they fire.
+    Greedy,
+    /// Keeps the same items as `Greedy`, ordered by the arrangement solver
+    /// against the upcoming enemies.
+    Solver,
+}
+
+impl BotPolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "random" => Some(Self::Random),
+            "greedy" => Some(Self::Greedy),
+            "solver" => Some(Self::Solver),
+            _ => None,
+        }
+    }
+
+    /// Returns the indices of `items` to put on the scroll, in order.
+    pub fn arrange(
+        &self,
+        items: &[ItemComponents],
+        item_definitions: &ItemDefinitions,
+        player: &SimPlayer,
+        enemies: &[SimEnemy],
+        rng: &mut impl RngCore,
+    ) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        match self {
+            BotPolicy::Random => {
+                order.shuffle(rng);
+                order.truncate(INVENTORY_SCROLL_SIZE);
+            }
+            BotPolicy::Greedy | BotPolicy::Solver => {
+                let rank = |i: &usize| match item_definitions
+                    .get(&items[*i].item_type)
+                    .and_then(|definition| definition.rarity)
+                {
+                    Some(Rarity::Mythic) => 3,
+                    Some(Rarity::Precious) => 2,
+                    Some(Rarity::Scarce) => 1,
+                    Some(Rarity::Mundane) | None => 0,
+                };
+                order.sort_by_key(|i| std::cmp::Reverse(rank(i)));
+                order.truncate(INVENTORY_SCROLL_SIZE);
+                order.sort_by_key(|i| items[*i].damage.is_some());
+            }
+        }
+        if *self == BotPolicy::Solver {
+            let kept: Vec<ItemComponents> = order.iter().map(|&i| items[i].clone()).collect();
+            let solution = solve_arrangement(&kept, player, enemies, SolverGoal::FastestKill);
+            order = solution.order.iter().map(|&i| order[i]).collect();
+        }
+        order
+    }
+}
+
+/// What happened during one simulated run.
+#[derive(Clone, Debug, Default)]
+pub struct RunResult {
+    pub seed: u64,
+    pub battle_wins: usize,
+    pub battle_turns: Vec<usize>,
+    pub offered: Vec<ItemType>,
+    pub picked: Vec<ItemType>,
+    pub stalemate: bool,
+}
+
+/// Plays a whole run headlessly, rolling loot and enemies from `seed` in the
+/// same order as the game, until the player loses or reaches `max_wins`.
+pub fn simulate_run(
+    item_definitions: &ItemDefinitions,
+    enemy_definitions: &EnemyDefinitions,
+    policy: BotPolicy,
+    seed: u64,
+    max_wins: usize,
+) -> RunResult {
+    let mut rng = Rng::from_seed(seed);
+    let mut battle_wins = BattleWins(0);
+    let mut hp = Hp::new(STARTING_PLAYER_HP);
+    let mut inventory: Vec<ItemComponents> = Vec::new();
+    let mut result = RunResult {
+        seed,
+        ..Default::default()
+    };
+
+    let mut bonus = LootBonus::default();
+    while battle_wins.0 < max_wins {
+        let loot = roll_loot(item_definitions, &battle_wins, bonus, &mut rng);
+        result.offered.extend(loot.iter().cloned());
+
+        let player = SimPlayer::new(hp);
+        let archetypes = enemy_definitions.encounter(&battle_wins, &mut rng);
+        bonus = loot_bonus(&archetypes);
+        let enemies: Vec<_> = archetypes
+            .iter()
+            .map(|archetype| SimEnemy::from_archetype(archetype, &battle_wins, item_definitions))
+            .collect();
+
+        let kept = inventory.len();
+        let mut candidates = inventory;
+        candidates.extend(
+            loot.iter()
+                .filter_map(|item_type| item_definitions.components(item_type)),
+        );
+        let order = policy.arrange(&candidates, item_definitions, &player, &enemies, &mut rng);
+        result.picked.extend(
+            order
+                .iter()
+                .filter(|&&i| i >= kept)
+                .map(|&i| candidates[i].item_type.clone()),
+        );
+        inventory = order.iter().map(|&i| candidates[i].clone()).collect();
+
+        let mut sim = BattleSim::new(inventory, player, enemies).with_seed(rng.next_u64());
+        let outcome = sim.run(MAX_BATTLE_ROUNDS);
+        result.battle_turns.push(sim.turns);
+        hp = sim.player.hp;
+        hp.expire_bonus(ModifierScope::Battle);
+        inventory = sim.items;
+        for item in inventory.iter_mut() {
+            item.expire_modifiers(ModifierScope::Battle);
+        }
+
+        match outcome {
+            Some(BattleOutcome::Won) => battle_wins.0 += 1,
+            Some(BattleOutcome::Lost) => break,
+            None => {
+                result.stalemate = true;
+                break;
+            }
+        }
+    }
+
+    result.battle_wins = battle_wins.0;
+    result
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BattleWinsCount {
+    pub battle_wins: usize,
+    pub runs: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ItemStats {
+    pub item: ItemType,
+    pub offered: usize,
+    pub picked: usize,
+    pub pick_rate: f32,
+    pub runs_with: usize,
+    pub average_wins_with: f32,
+    /// Average wins of runs that picked the item minus those that did not.
+    pub win_contribution: f32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BalanceReport {
+    pub policy: BotPolicy,
+    pub runs: usize,
+    pub average_battle_wins: f32,
+    pub stalemates: usize,
+    pub average_turns_per_battle: f32,
+    pub battle_wins: Vec<BattleWinsCount>,
+    pub items: Vec<ItemStats>,
+}
+
+fn average(total: usize, count: usize) -> f32 {
+    if count == 0 {
+        0.
+    } else {
+        total as f32 / count as f32
+    }
+}
+
+impl BalanceReport {
+    pub fn new(
+        policy: BotPolicy,
+        results: &[RunResult],
+        item_definitions: &ItemDefinitions,
+    ) -> Self {
+        let runs = results.len();
+        let total_wins: usize = results.iter().map(|r| r.battle_wins).sum();
+        let max_wins = results.iter().map(|r| r.battle_wins).max().unwrap_or(0);
+        let battles: usize = results.iter().map(|r| r.battle_turns.len()).sum();
+        let turns: usize = results.iter().flat_map(|r| r.battle_turns.iter()).sum();
+
+        let battle_wins = (0..=max_wins)
+            .map(|wins| BattleWinsCount {
+                battle_wins: wins,
+                runs: results.iter().filter(|r| r.battle_wins == wins).count(),
+            })
+            .collect();
+
+        let items = item_definitions
+            .items
+            .iter()
+            .map(|definition| {
+                let item = &definition.id;
+                let count = |items: &[ItemType]| items.iter().filter(|i| *i == item).count();
+                let offered = results.iter().map(|r| count(&r.offered)).sum();
+                let picked = results.iter().map(|r| count(&r.picked)).sum();
+                let (with, without): (Vec<&RunResult>, Vec<&RunResult>) =
+                    results.iter().partition(|r| r.picked.contains(item));
+                let wins_with = average(with.iter().map(|r| r.battle_wins).sum(), with.len());
+                let wins_without =
+                    average(without.iter().map(|r| r.battle_wins).sum(), without.len());
+                ItemStats {
+                    item: item.clone(),
+                    offered,
+                    picked,
+                    pick_rate: average(picked, offered),
+                    runs_with: with.len(),
+                    average_wins_with: wins_with,
+                    win_contribution: if with.is_empty() || without.is_empty() {
+                        0.
+                    } else {
+                        wins_with - wins_without
+                    },
+                }
+            })
+            .collect();
+
+        Self {
+            policy,
+            runs,
+            average_battle_wins: average(total_wins, runs),
+            stalemates: results.iter().filter(|r| r.stalemate).count(),
+            average_turns_per_battle: average(turns, battles),
+            battle_wins,
+            items,
+        }
+    }
+
+    pub fn summary_csv(&self) -> String {
+        format!(
+            "runs,average_battle_wins,stalemates,average_turns_per_battle\n{},{:.3},{},{:.3}\n",
+            self.runs, self.average_battle_wins, self.stalemates, self.average_turns_per_battle,
+        )
+    }
+
+    pub fn battle_wins_csv(&self) -> String {
+        let mut csv = "battle_wins,runs\n".to_string();
+        for count in self.battle_wins.iter() {
+            csv.push_str(&format!("{},{}\n", count.battle_wins, count.runs));
+        }
+        csv
+    }
+
+    pub fn items_csv(&self) -> String {
+        let mut csv =
+            "item,offered,picked,pick_rate,runs_with,average_wins_with,win_contribution\n"
+                .to_string();
+        for stats in self.items.iter() {
+            csv.push_str(&format!(
+                "{},{},{},{:.3},{},{:.3},{:.3}\n",
+                stats.item,
+                stats.offered,
+                stats.picked,
+                stats.pick_rate,
+                stats.runs_with,
+                stats.average_wins_with,
+                stats.win_contribution,
+            ));
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item_definitions() -> ItemDefinitions {
+        ron::from_str(
+            r#"(
+                starting_loot: [],
+                items: [
+                    (id: "Cutlass", name: "Cutlass", icon: 0),
+                    (id: "Grog", name: "Grog", icon: 0),
+                ],
+            )"#,
+        )
+        .unwrap()
+    }
+
+    fn run(
+        battle_wins: usize,
+        battle_turns: &[usize],
+        offered: &[&str],
+        picked: &[&str],
+    ) -> RunResult {
+        let item_types = |ids: &[&str]| ids.iter().map(|&id| ItemType::new(id)).collect();
+        RunResult {
+            battle_wins,
+            battle_turns: battle_turns.to_vec(),
+            offered: item_types(offered),
+            picked: item_types(picked),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reports_aggregate_the_runs() {
+        let results = [
+            run(2, &[4, 6, 2], &["Cutlass", "Grog"], &["Cutlass"]),
+            run(0, &[8], &["Cutlass", "Grog"], &["Grog"]),
+            run(1, &[5, 5], &["Cutlass"], &["Cutlass"]),
+        ];
+        let report = BalanceReport::new(BotPolicy::Greedy, &results, &item_definitions());
+        assert_eq!(report.runs, 3);
+        assert_eq!(report.average_battle_wins, 1.);
+        assert_eq!(report.average_turns_per_battle, 5.);
+        let counts: Vec<_> = report.battle_wins.iter().map(|c| c.runs).collect();
+        assert_eq!(counts, [1, 1, 1]);
+
+        let cutlass = &report.items[0];
+        assert_eq!(
+            (cutlass.offered, cutlass.picked, cutlass.runs_with),
+            (3, 2, 2)
+        );
+        assert!((cutlass.pick_rate - 2. / 3.).abs() < 1e-6);
+        assert_eq!(cutlass.win_contribution, 1.5);
+        let grog = &report.items[1];
+        assert_eq!(grog.pick_rate, 0.5);
+        assert_eq!(grog.win_contribution, -1.5);
+
+        assert_eq!(
+            report.summary_csv(),
+            "runs,average_battle_wins,stalemates,average_turns_per_battle\n3,1.000,0,5.000\n"
+        );
+    }
+
+    #[test]
+    fn items_nobody_skipped_contribute_nothing() {
+        let results = [run(1, &[3], &["Grog"], &["Grog"])];
+        let report = BalanceReport::new(BotPolicy::Greedy, &results, &item_definitions());
+        assert_eq!(report.items[1].win_contribution, 0.);
+        assert_eq!(report.items[0].pick_rate, 0.);
+    }
+}
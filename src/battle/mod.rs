@@ -0,0 +1,648 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::RngCore;
+
+use crate::{
+    assets::{GameFonts, GameSprites, ICON_INDEX_SCROLL_MARKER},
+    common::Hp,
+    enemy::{
+        spawn_enemy_scroll_ui, Enemy, EnemyArchetype, EnemyBehaviour, EnemyScrollUI, EnemySlot,
+    },
+    inventory::InventoryScrollUI,
+    items::{
+        abilities::{Ability, AbilityKind, Cursed, Damage, ModifierScope},
+        definitions::ItemDefinitions,
+        ItemComponentsQuery, ItemComponentsQueryReadOnly,
+    },
+    log::LogMessageEvent,
+    player::{Player, PlayerStats},
+    replay::Replay,
+    rng::Rng,
+    status::StatusEffects,
+    ui::{BottomCenterUI, FONT_COLOR, FONT_SIZE},
+    AppState, BattleWins,
+};
+
+use self::sim::{
+    BattleOutcome, BattleSim, EffectStat, EffectTarget, Side, SimEffect, SimEnemy, SimEvent,
+    SimPlayer,
+};
+
+pub mod sim;
+
+const SCROLL_MARKER_TOP: f32 = -2.;
+const SCROLL_MARKER_SPEED: f32 = 5.;
+
+pub struct BattlePlugin;
+
+impl Plugin for BattlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BattleWins>()
+            .add_event::<BattleEvent>()
+            .add_event::<UseItemInput>()
+            .add_event::<CycleTargetInput>()
+            .insert_state(BattleState::PlayerTurn)
+            .configure_sets(
+                Update,
+                (
+                    PlayerTurnSet
+                        .run_if(in_state(AppState::Battling))
+                        .run_if(in_state(BattleState::PlayerTurn)),
+                    EnemyTurnSet
+                        .run_if(in_state(AppState::Battling))
+                        .run_if(in_state(BattleState::EnemyTurn)),
+                ),
+            )
+            .add_systems(
+                OnEnter(AppState::Battling),
+                (
+                    setup_battle.after(spawn_enemy_scroll_ui),
+                    spawn_turn_counter_ui.after(spawn_enemy_scroll_ui),
+                ),
+            )
+            .add_systems(
+                OnExit(AppState::Battling),
+                (cleanup_battle, (expire_battle_buffs, lift_curses).chain()),
+            )
+            .add_systems(
+                Update,
+                update_turn_counter_ui.run_if(resource_exists_and_changed::<ActiveBattle>),
+            )
+            .add_systems(
+                Update,
+                refresh_battle_items.run_if(
+                    resource_exists::<ActiveBattle>
+                        .and_then(resource_exists_and_changed::<ItemDefinitions>),
+                ),
+            )
+            .add_systems(
+                OnExit(BattleState::EnemyTurn),
+                (check_battle_end,).run_if(in_state(AppState::Battling)),
+            )
+            .add_systems(
+                OnEnter(BattleState::PlayerTurn),
+                start_player_turn
+                    .run_if(in_state(AppState::Battling).and_then(resource_exists::<ActiveBattle>)),
+            )
+            .add_systems(
+                OnEnter(BattleState::EnemyTurn),
+                start_enemy_turn.run_if(in_state(AppState::Battling)),
+            )
+            .add_systems(
+                Update,
+                (
+                    enemy_turn,
+                    sync_battle.run_if(resource_exists_and_changed::<ActiveBattle>),
+                )
+                    .chain()
+                    .in_set(EnemyTurnSet),
+            )
+            .add_systems(
+                Update,
+                (
+                    read_use_item_input.run_if(not(resource_exists::<Replay>)),
+                    player_turn_cycle_target,
+                    player_turn_use_item,
+                    sync_battle.run_if(resource_exists_and_changed::<ActiveBattle>),
+                    update_scroll_marker_ui_pos,
+                    animate_scroll_marker,
+                    check_battle_end,
+                )
+                    .chain()
+                    .in_set(PlayerTurnSet),
+            );
+    }
+}
+
+#[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BattleState {
+    PlayerTurn,
+    EnemyTurn,
+    BattleEnd,
+}
+
+#[derive(Event, Clone, Copy)]
+pub enum BattleEvent {
+    PlayerHurt(i32),
+    PlayerHeal(i32),
+    EnemyHurt(usize, i32),
+    EnemyHeal(usize, i32),
+    EnemyAttack(usize),
+    Click(Side),
+    Misfire(Side),
+    Reload(Side),
+    Stunned(Side),
+    Effect(BattleEffect),
+}
+
+/// A `SimEffect` with its sides and items resolved to entities.
+#[derive(Clone, Copy, Debug)]
+pub struct BattleEffect {
+    /// The player or enemy behind the change.
+    pub source: Entity,
+    pub item: Option<Entity>,
+    pub ability: Option<AbilityKind>,
+    /// The player, enemy or item that changed.
+    pub target: Entity,
+    pub stat: EffectStat,
+    pub before: i32,
+    pub after: i32,
+}
+
+/// Asks for the item under the scroll marker to be used on the player's turn.
+#[derive(Event, Clone, Copy)]
+pub struct UseItemInput;
+
+/// Asks for the player's attacks to aim at the next enemy still standing.
+#[derive(Event, Clone, Copy)]
+pub struct CycleTargetInput;
+
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PlayerTurnSet;
+
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct EnemyTurnSet;
+
+/// The battle currently being fought, along with the scroll entities that
+/// mirror `sim.items` and each of `sim.enemies`.
+#[derive(Resource)]
+pub struct ActiveBattle {
+    pub sim: BattleSim,
+    player: Entity,
+    items: Vec<Entity>,
+    enemies: Vec<Entity>,
+    enemy_items: Vec<Vec<Entity>>,
+}
+
+impl ActiveBattle {
+    fn side_entity(&self, side: Side) -> Entity {
+        match side {
+            Side::Player => self.player,
+            Side::Enemy(index) => self.enemies[index],
+        }
+    }
+
+    fn item_entity(&self, side: Side, index: usize) -> Option<Entity> {
+        let items = match side {
+            Side::Player => &self.items,
+            Side::Enemy(enemy) => &self.enemy_items[enemy],
+        };
+        items.get(index).copied()
+    }
+
+    fn resolve(&self, effect: SimEffect) -> Option<BattleEffect> {
+        let target = match effect.target {
+            EffectTarget::Side(side) => self.side_entity(side),
+            EffectTarget::Item(side, index) => self.item_entity(side, index)?,
+        };
+        Some(BattleEffect {
+            source: self.side_entity(effect.source),
+            item: effect
+                .item
+                .and_then(|index| self.item_entity(effect.source, index)),
+            ability: effect.ability,
+            target,
+            stat: effect.stat,
+            before: effect.before,
+            after: effect.after,
+        })
+    }
+
+    fn dispatch(
+        &mut self,
+        events: Vec<SimEvent>,
+        battle_event_ew: &mut EventWriter<BattleEvent>,
+    ) -> Vec<Entity> {
+        let mut consumed = Vec::new();
+        for event in events {
+            match event {
+                SimEvent::PlayerHurt(amount) => {
+                    battle_event_ew.send(BattleEvent::PlayerHurt(amount));
+                }
+                SimEvent::PlayerHeal(amount) => {
+                    battle_event_ew.send(BattleEvent::PlayerHeal(amount));
+                }
+                SimEvent::EnemyHurt(enemy, amount) => {
+                    battle_event_ew.send(BattleEvent::EnemyHurt(enemy, amount));
+                }
+                SimEvent::EnemyHeal(enemy, amount) => {
+                    battle_event_ew.send(BattleEvent::EnemyHeal(enemy, amount));
+                }
+                SimEvent::EnemyAttack(enemy) => {
+                    battle_event_ew.send(BattleEvent::EnemyAttack(enemy));
+                }
+                SimEvent::Click(side) => {
+                    battle_event_ew.send(BattleEvent::Click(side));
+                }
+                SimEvent::Misfire(side) => {
+                    battle_event_ew.send(BattleEvent::Misfire(side));
+                }
+                SimEvent::Reload(side) => {
+                    battle_event_ew.send(BattleEvent::Reload(side));
+                }
+                SimEvent::Stunned(side) => {
+                    battle_event_ew.send(BattleEvent::Stunned(side));
+                }
+                SimEvent::Effect(effect) => {
+                    if let Some(effect) = self.resolve(effect) {
+                        battle_event_ew.send(BattleEvent::Effect(effect));
+                    }
+                }
+                SimEvent::ItemConsumed(Side::Player, index) => {
+                    consumed.push(self.items.remove(index))
+                }
+                SimEvent::ItemConsumed(Side::Enemy(enemy), index) => {
+                    consumed.push(self.enemy_items[enemy].remove(index))
+                }
+            }
+        }
+        consumed
+    }
+}
+
+#[derive(Component)]
+struct EnemyTurnTimer(Timer);
+
+#[derive(Component, Default)]
+struct ScrollMarker;
+
+/// Shows the current turn, and whether any enemy has enraged.
+#[derive(Component)]
+struct TurnCounterUI;
+
+#[derive(Bundle, Default)]
+struct ScrollMarkerBundle {
+    scroll_marker: ScrollMarker,
+    atlas_image_bundle: AtlasImageBundle,
+}
+
+fn setup_battle(
+    mut commands: Commands,
+    mut battle_state: ResMut<NextState<BattleState>>,
+    game_sprites: Res<GameSprites>,
+    scroll_ui_q: Query<&Children, With<InventoryScrollUI>>,
+    enemy_scroll_ui_q: Query<(&Children, &EnemySlot), With<EnemyScrollUI>>,
+    items_q: Query<ItemComponentsQueryReadOnly>,
+    player_q: Query<(Entity, &Hp), With<Player>>,
+    mut rng: ResMut<Rng>,
+    mut log_message_ew: EventWriter<LogMessageEvent>,
+    enemy_q: Query<
+        (
+            Entity,
+            &Hp,
+            &Damage,
+            &EnemyBehaviour,
+            &EnemyArchetype,
+            &EnemySlot,
+        ),
+        With<Enemy>,
+    >,
+) {
+    battle_state.set(BattleState::PlayerTurn);
+    log_message_ew.send(LogMessageEvent("Turn 1".to_string()));
+    let scroll_marker_ui = commands
+        .spawn(ScrollMarkerBundle {
+            atlas_image_bundle: AtlasImageBundle {
+                image: UiImage::new(game_sprites.items_tile_sheet.clone()),
+                texture_atlas: TextureAtlas {
+                    layout: game_sprites.items_tile_layout.clone(),
+                    index: ICON_INDEX_SCROLL_MARKER,
+                },
+                style: Style {
+                    width: Val::Px(16.),
+                    height: Val::Px(16.),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(SCROLL_MARKER_TOP),
+                    ..default()
+                },
+                ..default()
+            },
+            ..default()
+        })
+        .id();
+
+    let scroll_items = |children: &Children| -> Vec<Entity> {
+        children
+            .iter()
+            .filter(|&&c| items_q.contains(c))
+            .copied()
+            .collect()
+    };
+    let items = scroll_ui_q
+        .get_single()
+        .map(scroll_items)
+        .unwrap_or_default();
+    let components = |items: &[Entity]| {
+        items
+            .iter()
+            .map(|&item| items_q.get(item).unwrap().components())
+            .collect()
+    };
+    if let Some(item) = items.first() {
+        commands.entity(scroll_marker_ui).set_parent(*item);
+    }
+
+    let mut enemy_q: Vec<_> = enemy_q.iter().collect();
+    enemy_q.sort_by_key(|(.., slot)| slot.0);
+    let mut enemies = Vec::new();
+    let mut enemy_items = Vec::new();
+    let mut sim_enemies = Vec::new();
+    for (entity, hp, damage, behaviour, archetype, slot) in enemy_q {
+        let items = enemy_scroll_ui_q
+            .iter()
+            .find(|(_, s)| *s == slot)
+            .map(|(children, _)| scroll_items(children))
+            .unwrap_or_default();
+        sim_enemies.push(
+            SimEnemy::new(hp.clone(), damage.clone(), *behaviour, components(&items))
+                .with_armor(archetype.armor)
+                .with_enrage(archetype.enrage)
+                .with_mechanics(archetype.mechanics.clone())
+                .with_affixes(archetype.affixes.clone()),
+        );
+        enemies.push(entity);
+        enemy_items.push(items);
+    }
+    let (player, player_hp) = player_q.single();
+    let sim = BattleSim::new(
+        components(&items),
+        SimPlayer::new(player_hp.clone()),
+        sim_enemies,
+    )
+    .with_seed(rng.next_u64());
+    commands.insert_resource(ActiveBattle {
+        sim,
+        player,
+        items,
+        enemies,
+        enemy_items,
+    });
+}
+
+fn read_use_item_input(
+    mut use_item_input_ew: EventWriter<UseItemInput>,
+    mut cycle_target_input_ew: EventWriter<CycleTargetInput>,
+    key_codes: Res<ButtonInput<KeyCode>>,
+) {
+    if key_codes.just_pressed(KeyCode::Tab) {
+        cycle_target_input_ew.send(CycleTargetInput);
+    }
+    if key_codes.just_pressed(KeyCode::Space) {
+        use_item_input_ew.send(UseItemInput);
+    }
+}
+
+fn player_turn_cycle_target(
+    mut battle: ResMut<ActiveBattle>,
+    mut cycle_target_input_er: EventReader<CycleTargetInput>,
+) {
+    for _ in cycle_target_input_er.read() {
+        battle.sim.cycle_target();
+    }
+}
+
+fn player_turn_use_item(
+    mut commands: Commands,
+    mut battle: ResMut<ActiveBattle>,
+    mut battle_event_ew: EventWriter<BattleEvent>,
+    mut battle_state: ResMut<NextState<BattleState>>,
+    mut use_item_input_er: EventReader<UseItemInput>,
+    scroll_marker_q: Query<Entity, With<ScrollMarker>>,
+) {
+    if use_item_input_er.read().last().is_some() {
+        let events = battle.sim.player_turn();
+        for item in battle.dispatch(events, &mut battle_event_ew) {
+            for scroll_marker in scroll_marker_q.iter() {
+                commands.entity(scroll_marker).remove_parent();
+            }
+            commands.entity(item).despawn_recursive();
+        }
+        battle_state.set(BattleState::EnemyTurn);
+    }
+}
+
+fn start_player_turn(
+    mut battle: ResMut<ActiveBattle>,
+    mut battle_event_ew: EventWriter<BattleEvent>,
+) {
+    let events = battle.sim.begin_player_turn();
+    battle.dispatch(events, &mut battle_event_ew);
+}
+
+fn start_enemy_turn(
+    mut commands: Commands,
+    mut battle: ResMut<ActiveBattle>,
+    mut battle_event_ew: EventWriter<BattleEvent>,
+) {
+    let events = battle.sim.begin_enemy_turn();
+    battle.dispatch(events, &mut battle_event_ew);
+    commands.spawn(EnemyTurnTimer(Timer::new(
+        Duration::from_secs_f32(0.5),
+        TimerMode::Once,
+    )));
+}
+
+fn enemy_turn(
+    mut commands: Commands,
+    mut log_message_ew: EventWriter<LogMessageEvent>,
+    mut battle: ResMut<ActiveBattle>,
+    mut battle_event_ew: EventWriter<BattleEvent>,
+    mut battle_state: ResMut<NextState<BattleState>>,
+    mut turn_timer_q: Query<(Entity, &mut EnemyTurnTimer)>,
+    time: Res<Time>,
+) {
+    let (entity, mut turn_timer) = turn_timer_q.single_mut();
+    turn_timer.0.tick(time.delta());
+    if turn_timer.0.just_finished() {
+        let events = battle.sim.enemy_turn();
+        for item in battle.dispatch(events, &mut battle_event_ew) {
+            commands.entity(item).despawn_recursive();
+        }
+        if battle.sim.outcome().is_none() {
+            log_message_ew.send(LogMessageEvent(format!("Turn {}", battle.sim.turns + 1)));
+        }
+        battle_state.set(BattleState::PlayerTurn);
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn sync_battle(
+    mut commands: Commands,
+    battle: Res<ActiveBattle>,
+    mut items_q: Query<ItemComponentsQuery, Without<Enemy>>,
+    mut player_q: Query<(&mut Hp, &mut PlayerStats, &mut StatusEffects), With<Player>>,
+    mut enemy_q: Query<(&mut Hp, &mut Damage, &mut StatusEffects), (With<Enemy>, Without<Player>)>,
+) {
+    let player_items = battle.items.iter().zip(battle.sim.items.iter());
+    let enemy_items = battle
+        .enemy_items
+        .iter()
+        .zip(battle.sim.enemies.iter())
+        .flat_map(|(entities, enemy)| entities.iter().zip(enemy.items.iter()));
+    for (&entity, components) in player_items.chain(enemy_items) {
+        if let Ok(mut item) = items_q.get_mut(entity) {
+            item.set(components);
+            // Bosses can curse items that had no curse of their own.
+            if let (None, Some(cursed)) = (&item.cursed, &components.cursed) {
+                commands.entity(entity).insert(cursed.clone());
+            }
+        }
+    }
+    if let Ok((mut hp, mut player_stats, mut statuses)) = player_q.get_single_mut() {
+        *hp = battle.sim.player.hp.clone();
+        *player_stats = battle.sim.player.stats.clone();
+        statuses.set_if_neq(battle.sim.player.statuses.clone());
+    }
+    for (&entity, enemy) in battle.enemies.iter().zip(battle.sim.enemies.iter()) {
+        if let Ok((mut hp, mut damage, mut statuses)) = enemy_q.get_mut(entity) {
+            *hp = enemy.hp.clone();
+            *damage = enemy.damage.clone();
+            statuses.set_if_neq(enemy.statuses.clone());
+        }
+    }
+}
+
+fn refresh_battle_items(mut battle: ResMut<ActiveBattle>, item_definitions: Res<ItemDefinitions>) {
+    let sim = &mut battle.sim;
+    let enemy_items = sim
+        .enemies
+        .iter_mut()
+        .flat_map(|enemy| enemy.items.iter_mut());
+    for item in sim.items.iter_mut().chain(enemy_items) {
+        if let Some(refreshed) = item_definitions.refresh(item) {
+            *item = refreshed;
+        }
+    }
+}
+
+fn check_battle_end(
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut battle_state: ResMut<NextState<BattleState>>,
+    mut battle_wins: ResMut<BattleWins>,
+    battle: Res<ActiveBattle>,
+) {
+    match battle.sim.outcome() {
+        Some(BattleOutcome::Lost) => {
+            next_app_state.set(AppState::GameOver);
+            battle_state.set(BattleState::BattleEnd);
+        }
+        Some(BattleOutcome::Won) => {
+            next_app_state.set(AppState::OrganizeInventory);
+            battle_state.set(BattleState::BattleEnd);
+            battle_wins.0 += 1;
+        }
+        None => (),
+    }
+}
+
+fn animate_scroll_marker(
+    mut scroll_marker_q: Query<&mut Style, With<ScrollMarker>>,
+    time: Res<Time>,
+) {
+    for mut style in scroll_marker_q.iter_mut() {
+        style.top =
+            Val::Px(f32::sin(time.elapsed_seconds() * SCROLL_MARKER_SPEED) + SCROLL_MARKER_TOP);
+    }
+}
+
+fn update_scroll_marker_ui_pos(
+    mut commands: Commands,
+    battle: Res<ActiveBattle>,
+    scroll_marker_q: Query<Entity, With<ScrollMarker>>,
+) {
+    let Ok(entity) = scroll_marker_q.get_single() else {
+        return;
+    };
+    if let Some(item) = battle.items.get(battle.sim.marker) {
+        commands.entity(entity).set_parent(*item);
+    }
+}
+
+fn expire_battle_buffs(
+    mut abilities_q: Query<&mut dyn Ability, Without<Enemy>>,
+    mut player_q: Query<&mut Hp, With<Player>>,
+) {
+    for abilities in abilities_q.iter_mut() {
+        for mut ability in abilities {
+            ability.modifier_mut().expire(ModifierScope::Battle);
+        }
+    }
+    if let Ok(mut hp) = player_q.get_single_mut() {
+        hp.expire_bonus(ModifierScope::Battle);
+    }
+}
+
+/// Lifts the curses bosses laid on items, once their modifiers have expired.
+fn lift_curses(mut commands: Commands, cursed_q: Query<(Entity, &Cursed), Without<Enemy>>) {
+    for (entity, cursed) in cursed_q.iter() {
+        if cursed.base == 0 && cursed.amount() == 0 {
+            commands.entity(entity).remove::<Cursed>();
+        }
+    }
+}
+
+fn spawn_turn_counter_ui(
+    mut commands: Commands,
+    game_fonts: Res<GameFonts>,
+    bottom_center_ui_q: Query<Entity, With<BottomCenterUI>>,
+) {
+    let style = TextStyle {
+        color: FONT_COLOR,
+        font_size: FONT_SIZE,
+        font: game_fonts.font.clone(),
+    };
+    let turn_counter_ui = commands
+        .spawn((
+            TurnCounterUI,
+            TextBundle::from_sections([
+                TextSection::new("Turn 1", style.clone()),
+                TextSection::new(
+                    "",
+                    TextStyle {
+                        color: Color::RED,
+                        ..style
+                    },
+                ),
+            ]),
+        ))
+        .id();
+    commands
+        .entity(bottom_center_ui_q.single())
+        .insert_children(0, &[turn_counter_ui]);
+}
+
+fn update_turn_counter_ui(
+    battle: Res<ActiveBattle>,
+    battle_state: Res<State<BattleState>>,
+    mut turn_counter_q: Query<&mut Text, With<TurnCounterUI>>,
+) {
+    let Ok(mut text) = turn_counter_q.get_single_mut() else {
+        return;
+    };
+    let enraged = battle
+        .sim
+        .enemies
+        .iter()
+        .any(|enemy| enemy.enraged > 0 && !enemy.hp.is_dead());
+    // `turns` counts the player's moves, so it only moves on to the next
+    // turn once the enemies have answered.
+    let turn = match battle_state.get() {
+        BattleState::PlayerTurn => battle.sim.turns + 1,
+        _ => battle.sim.turns,
+    };
+    text.sections[0].value = format!("Turn {turn}");
+    text.sections[1].value = if enraged { " Enraged!" } else { "" }.to_string();
+}
+
+fn cleanup_battle(
+    mut commands: Commands,
+    battle_ui_q: Query<Entity, Or<(With<ScrollMarker>, With<TurnCounterUI>)>>,
+) {
+    for entity in battle_ui_q.iter() {
+        if let Some(e) = commands.get_entity(entity) {
+            e.despawn_recursive();
+        }
+    }
+    commands.remove_resource::<ActiveBattle>();
+}
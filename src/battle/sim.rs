@@ -0,0 +1,1483 @@
+use rand::{seq::SliceRandom, Rng as _};
+
+use crate::{
+    common::Hp,
+    enemy::{BossMechanic, EnemyAffix, EnemyArchetype, EnemyBehaviour, EnemyBundle, Enrage},
+    items::{
+        abilities::{
+            Ability, AbilityKind, AbilityTarget, Cursed, Damage, ModifierScope, TargetFilter,
+        },
+        attributes::Attribute,
+        definitions::ItemDefinitions,
+        ItemComponents, ItemType,
+    },
+    player::PlayerStats,
+    rng::Rng,
+    status::{StatusEffect, StatusEffects, StatusKind},
+    BattleWins,
+};
+
+pub use self::damage::{DamageEvent, DamageKind, HealEvent};
+
+mod damage;
+
+#[derive(Clone, Debug)]
+pub struct SimPlayer {
+    pub hp: Hp,
+    pub stats: PlayerStats,
+    pub statuses: StatusEffects,
+}
+
+impl SimPlayer {
+    pub fn new(hp: Hp) -> Self {
+        Self {
+            hp,
+            stats: PlayerStats::default(),
+            statuses: StatusEffects::default(),
+        }
+    }
+}
+
+/// The enemy's side of a battle. Enemies with an empty scroll fall back to
+/// hitting with their flat `damage`.
+#[derive(Clone, Debug)]
+pub struct SimEnemy {
+    pub hp: Hp,
+    pub damage: Damage,
+    pub behaviour: EnemyBehaviour,
+    pub stats: PlayerStats,
+    pub statuses: StatusEffects,
+    pub items: Vec<ItemComponents>,
+    pub marker: usize,
+    pub enrage: Option<Enrage>,
+    /// How many times `enrage` has already triggered.
+    pub enraged: usize,
+    pub mechanics: Vec<BossMechanic>,
+    pub affixes: Vec<EnemyAffix>,
+}
+
+impl SimEnemy {
+    pub fn new(
+        hp: Hp,
+        damage: Damage,
+        behaviour: EnemyBehaviour,
+        items: Vec<ItemComponents>,
+    ) -> Self {
+        Self {
+            hp,
+            damage,
+            behaviour,
+            stats: PlayerStats::default(),
+            statuses: StatusEffects::default(),
+            items,
+            marker: 0,
+            enrage: None,
+            enraged: 0,
+            mechanics: Vec::new(),
+            affixes: Vec::new(),
+        }
+    }
+
+    pub fn from_archetype(
+        archetype: &EnemyArchetype,
+        battle_wins: &BattleWins,
+        item_definitions: &ItemDefinitions,
+    ) -> Self {
+        let EnemyBundle {
+            hp,
+            damage,
+            behaviour,
+            ..
+        } = EnemyBundle::from_archetype(archetype, battle_wins);
+        Self::new(
+            hp,
+            damage,
+            behaviour,
+            archetype
+                .items
+                .iter()
+                .filter_map(|item_type| item_definitions.components(item_type))
+                .collect(),
+        )
+        .with_armor(archetype.armor)
+        .with_enrage(archetype.enrage)
+        .with_mechanics(archetype.mechanics.clone())
+        .with_affixes(archetype.affixes.clone())
+    }
+
+    pub fn with_armor(mut self, armor: i32) -> Self {
+        self.stats.armor = armor;
+        self
+    }
+
+    pub fn with_enrage(mut self, enrage: Option<Enrage>) -> Self {
+        self.enrage = enrage;
+        self
+    }
+
+    pub fn with_mechanics(mut self, mechanics: Vec<BossMechanic>) -> Self {
+        self.mechanics = mechanics;
+        self
+    }
+
+    /// Adds elite affixes, on top of any armor the enemy already has.
+    pub fn with_affixes(mut self, affixes: Vec<EnemyAffix>) -> Self {
+        for affix in affixes.iter() {
+            if let EnemyAffix::Armored(armor) = affix {
+                self.stats.armor += armor;
+            }
+        }
+        self.affixes = affixes;
+        self
+    }
+
+    pub fn has_affix(&self, affix: EnemyAffix) -> bool {
+        self.affixes.contains(&affix)
+    }
+
+    /// How many times the enemy acts each turn.
+    pub fn actions(&self) -> usize {
+        if self.has_affix(EnemyAffix::Swift) {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/// What an enemy means to do on its next turn, with how much of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Intent {
+    Attack(i32),
+    Buff(i32),
+    Defend(i32),
+    Debuff(i32),
+    /// Using something that fits none of the above.
+    Unknown,
+}
+
+/// The player, or the enemy at this index of `BattleSim::enemies`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Player,
+    Enemy(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimEvent {
+    PlayerHurt(i32),
+    PlayerHeal(i32),
+    EnemyHurt(usize, i32),
+    EnemyHeal(usize, i32),
+    EnemyAttack(usize),
+    ItemConsumed(Side, usize),
+    /// A firearm was used with nothing loaded.
+    Click(Side),
+    Misfire(Side),
+    Reload(Side),
+    /// A turn lost to stun.
+    Stunned(Side),
+    Effect(SimEffect),
+}
+
+/// A single value something in battle changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimEffect {
+    pub source: Side,
+    /// Index of the item behind the change in `source`'s scroll, as it stood
+    /// when the effect happened.
+    pub item: Option<usize>,
+    pub ability: Option<AbilityKind>,
+    pub target: EffectTarget,
+    pub stat: EffectStat,
+    pub before: i32,
+    pub after: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EffectTarget {
+    Side(Side),
+    /// An item in the given side's scroll.
+    Item(Side, usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EffectStat {
+    Hp,
+    MaxHp,
+    SeaLegs,
+    /// The amount of one of the target item's abilities.
+    Ability(AbilityKind),
+    Ammo,
+    Uses,
+    Status(StatusKind),
+    Armor,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BattleOutcome {
+    Won,
+    Lost,
+}
+
+/// A single battle resolved as plain data, without any ECS or rendering.
+///
+/// The player uses the item under `marker` each turn, hitting the enemy at
+/// `target`, and every enemy still standing answers with the item under its
+/// own marker. Items keep any modifiers, ammo and uses they gained once the
+/// battle is over.
+#[derive(Clone, Debug)]
+pub struct BattleSim {
+    pub player: SimPlayer,
+    pub enemies: Vec<SimEnemy>,
+    pub items: Vec<ItemComponents>,
+    pub marker: usize,
+    pub target: usize,
+    pub turns: usize,
+    /// How many times the player's marker has wrapped back to the start.
+    pub passes: usize,
+    /// Draws for `TargetFilter::Random`.
+    pub rng: Rng,
+}
+
+impl BattleSim {
+    pub fn new(items: Vec<ItemComponents>, player: SimPlayer, enemies: Vec<SimEnemy>) -> Self {
+        Self {
+            player,
+            enemies,
+            items,
+            marker: 0,
+            target: 0,
+            turns: 0,
+            passes: 0,
+            rng: Rng::from_seed(0),
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::from_seed(seed);
+        self
+    }
+
+    pub fn from_item_types(
+        item_definitions: &ItemDefinitions,
+        item_types: &[ItemType],
+        player: SimPlayer,
+        enemies: Vec<SimEnemy>,
+    ) -> Self {
+        Self::new(
+            item_types
+                .iter()
+                .filter_map(|item_type| item_definitions.components(item_type))
+                .collect(),
+            player,
+            enemies,
+        )
+    }
+
+    pub fn outcome(&self) -> Option<BattleOutcome> {
+        if self.player.hp.is_dead() {
+            Some(BattleOutcome::Lost)
+        } else if self.enemies.iter().all(|enemy| enemy.hp.is_dead()) {
+            Some(BattleOutcome::Won)
+        } else {
+            None
+        }
+    }
+
+    /// Total hp the enemies have left.
+    pub fn enemy_hp_left(&self) -> i32 {
+        self.enemies
+            .iter()
+            .map(|enemy| enemy.hp.current.max(0))
+            .sum()
+    }
+
+    /// What the enemy at `index` will do on its next turn, one intent per
+    /// action and in order, going by the items from its marker on. Empty once
+    /// it is down.
+    pub fn enemy_intents(&self, index: usize) -> Vec<Intent> {
+        let Some(enemy) = self.enemies.get(index).filter(|enemy| !enemy.hp.is_dead()) else {
+            return Vec::new();
+        };
+        let mut scroll = enemy.items.clone();
+        let mut marker = enemy.marker;
+        (0..enemy.actions())
+            .map(|_| {
+                if marker >= scroll.len() {
+                    return Intent::Attack(enemy.damage.amount());
+                }
+                let intent = Self::item_intent(&scroll, marker);
+                let reload = Self::reload_amount(&scroll, marker);
+                let item = &mut scroll[marker];
+                if let (Some(flintlock), true) = (item.flintlock.as_mut(), item.damage.is_some()) {
+                    if flintlock.loaded() > 0 {
+                        flintlock.fire();
+                    } else if let (true, Some(amount)) = (flintlock.reloads(), reload) {
+                        flintlock.load(amount);
+                    }
+                }
+                if item.consumable.as_ref().is_some_and(|uses| uses.0 <= 1) {
+                    scroll.remove(marker);
+                } else {
+                    marker += 1;
+                }
+                marker = match scroll.len() {
+                    0 => 0,
+                    len => marker % len,
+                };
+                intent
+            })
+            .collect()
+    }
+
+    /// An empty firearm won't attack: it reloads if it can, which shows as a
+    /// buff, and otherwise only clicks.
+    fn item_intent(scroll: &[ItemComponents], index: usize) -> Intent {
+        let item = &scroll[index];
+        let loaded = (item.flintlock.as_ref()).is_none_or(|flintlock| flintlock.loaded() > 0);
+        let intents: [(fn(i32) -> Intent, &[AbilityKind]); 4] = [
+            (Intent::Attack, &[AbilityKind::Damage]),
+            (
+                Intent::Debuff,
+                &[
+                    AbilityKind::Poison,
+                    AbilityKind::Bleed,
+                    AbilityKind::Burn,
+                    AbilityKind::Stun,
+                ],
+            ),
+            (
+                Intent::Defend,
+                &[AbilityKind::SeaLegs, AbilityKind::Hearties],
+            ),
+            (
+                Intent::Buff,
+                &[
+                    AbilityKind::Heave,
+                    AbilityKind::Swashbuckle,
+                    AbilityKind::Jolly,
+                    AbilityKind::Buff,
+                    AbilityKind::Vitality,
+                ],
+            ),
+        ];
+        intents
+            .into_iter()
+            .find_map(|(intent, kinds)| {
+                let abilities: Vec<_> = kinds
+                    .iter()
+                    .filter(|&&kind| loaded || kind != AbilityKind::Damage)
+                    .filter_map(|&kind| item.ability(kind))
+                    .collect();
+                (!abilities.is_empty()).then(|| intent(abilities.iter().map(|a| a.amount()).sum()))
+            })
+            .or_else(|| {
+                let reloads =
+                    (item.flintlock.as_ref()).is_some_and(|flintlock| flintlock.reloads());
+                let reload = reloads
+                    .then(|| Self::reload_amount(scroll, index))
+                    .flatten();
+                reload.map(|amount| Intent::Buff(amount as i32))
+            })
+            .unwrap_or(Intent::Unknown)
+    }
+
+    /// Moves `target` on to the next enemy still standing.
+    pub fn cycle_target(&mut self) {
+        let len = self.enemies.len();
+        if let Some(target) = (1..=len)
+            .map(|offset| (self.target + offset) % len)
+            .find(|&i| !self.enemies[i].hp.is_dead())
+        {
+            self.target = target;
+        }
+    }
+
+    /// Ticks the player's status effects as their turn starts.
+    pub fn begin_player_turn(&mut self) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        self.expire_turn_buffs(Side::Player);
+        self.tick_statuses(Side::Player, &mut events);
+        events
+    }
+
+    /// Ticks the status effects of every enemy still standing as their turn
+    /// starts.
+    pub fn begin_enemy_turn(&mut self) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        for index in 0..self.enemies.len() {
+            if !self.enemies[index].hp.is_dead() {
+                self.expire_turn_buffs(Side::Enemy(index));
+                self.tick_statuses(Side::Enemy(index), &mut events);
+            }
+        }
+        events
+    }
+
+    pub fn player_turn(&mut self) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        self.turns += 1;
+        if self.statuses_mut(Side::Player).skip_turn() {
+            events.push(SimEvent::Stunned(Side::Player));
+        } else {
+            self.take_turn(Side::Player, &mut events);
+            self.bleed(Side::Player, &mut events);
+        }
+        for index in 0..self.enemies.len() {
+            self.wear_off_sea_legs(Side::Enemy(index));
+        }
+        if (self.enemies.get(self.target)).is_some_and(|enemy| enemy.hp.is_dead()) {
+            self.cycle_target();
+        }
+        events
+    }
+
+    pub fn enemy_turn(&mut self) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        for index in 0..self.enemies.len() {
+            if self.enemies[index].hp.is_dead() || self.player.hp.is_dead() {
+                continue;
+            }
+            self.enrage(index, &mut events);
+            if self.statuses_mut(Side::Enemy(index)).skip_turn() {
+                events.push(SimEvent::Stunned(Side::Enemy(index)));
+                continue;
+            }
+            self.enemy_act(index, &mut events);
+        }
+        self.wear_off_sea_legs(Side::Player);
+        events
+    }
+
+    fn enemy_act(&mut self, index: usize, events: &mut Vec<SimEvent>) {
+        for mechanic in self.enemies[index].mechanics.clone() {
+            self.boss_mechanic(index, mechanic, events);
+        }
+        for _ in 0..self.enemies[index].actions() {
+            if self.player.hp.is_dead() {
+                break;
+            }
+            self.enemy_attack(index, events);
+        }
+        self.bleed(Side::Enemy(index), events);
+        if let EnemyBehaviour::Berserk(rage) = self.enemies[index].behaviour {
+            self.grow_damage(index, rage, events);
+        }
+    }
+
+    fn enemy_attack(&mut self, index: usize, events: &mut Vec<SimEvent>) {
+        events.push(SimEvent::EnemyAttack(index));
+        let enemy = &self.enemies[index];
+        if enemy.items.is_empty() {
+            let event = DamageEvent {
+                source: Side::Enemy(index),
+                item: None,
+                ability: Some(AbilityKind::Damage),
+                target: Side::Player,
+                amount: enemy.damage.amount(),
+                kind: DamageKind::Attack,
+            };
+            self.damage(event, events);
+        } else {
+            self.take_turn(Side::Enemy(index), events);
+        }
+    }
+
+    fn boss_mechanic(&mut self, index: usize, mechanic: BossMechanic, events: &mut Vec<SimEvent>) {
+        let side = Side::Enemy(index);
+        match mechanic {
+            BossMechanic::CurseItem(amount) => {
+                if self.items.is_empty() {
+                    return;
+                }
+                let i = self.rng.gen_range(0..self.items.len());
+                let cursed = self.items[i].cursed.get_or_insert_with(Cursed::default);
+                let before = cursed.amount();
+                cursed.modifier.add(ModifierScope::Battle, amount);
+                events.push(SimEvent::Effect(SimEffect {
+                    source: side,
+                    item: None,
+                    ability: Some(AbilityKind::Cursed),
+                    target: EffectTarget::Item(Side::Player, i),
+                    stat: EffectStat::Ability(AbilityKind::Cursed),
+                    before,
+                    after: cursed.amount(),
+                }));
+            }
+            BossMechanic::GainArmor { every, amount } => {
+                if every == 0 || !self.turns.is_multiple_of(every) {
+                    return;
+                }
+                let armor = &mut self.stats_mut(side).armor;
+                let before = *armor;
+                *armor += amount;
+                let after = *armor;
+                events.push(SimEvent::Effect(SimEffect {
+                    source: side,
+                    item: None,
+                    ability: None,
+                    target: EffectTarget::Side(side),
+                    stat: EffectStat::Armor,
+                    before,
+                    after,
+                }));
+            }
+        }
+    }
+
+    /// Makes the enemy hit harder for every time its enrage rule has
+    /// triggered since its last turn.
+    fn enrage(&mut self, index: usize, events: &mut Vec<SimEvent>) {
+        let enemy = &self.enemies[index];
+        let Some(enrage) = enemy.enrage else {
+            return;
+        };
+        let stacks = enrage.stacks(self.turns, self.passes);
+        if stacks > enemy.enraged {
+            let rage = enrage.damage * (stacks - enemy.enraged) as i32;
+            self.enemies[index].enraged = stacks;
+            self.grow_damage(index, rage, events);
+        }
+    }
+
+    /// Adds `rage` to the enemy's own damage and every damage item on its
+    /// scroll for the rest of the battle.
+    fn grow_damage(&mut self, index: usize, rage: i32, events: &mut Vec<SimEvent>) {
+        let side = Side::Enemy(index);
+        let effect = |target, before, after| {
+            SimEvent::Effect(SimEffect {
+                source: side,
+                item: None,
+                ability: None,
+                target,
+                stat: EffectStat::Ability(AbilityKind::Damage),
+                before,
+                after,
+            })
+        };
+        let enemy = &mut self.enemies[index];
+        let before = enemy.damage.amount();
+        enemy.damage.modifier.add(ModifierScope::Battle, rage);
+        events.push(effect(
+            EffectTarget::Side(side),
+            before,
+            enemy.damage.amount(),
+        ));
+        for (i, item) in enemy.items.iter_mut().enumerate() {
+            if let Some(damage) = item.damage.as_mut() {
+                let before = damage.amount();
+                damage.modifier.add(ModifierScope::Battle, rage);
+                events.push(effect(EffectTarget::Item(side, i), before, damage.amount()));
+            }
+        }
+    }
+
+    /// Plays the player's turn followed by the enemy's, unless the battle
+    /// ended in between.
+    pub fn round(&mut self) -> Vec<SimEvent> {
+        let steps: [fn(&mut Self) -> Vec<SimEvent>; 4] = [
+            Self::begin_player_turn,
+            Self::player_turn,
+            Self::begin_enemy_turn,
+            Self::enemy_turn,
+        ];
+        let mut events = Vec::new();
+        for step in steps {
+            if self.outcome().is_some() {
+                break;
+            }
+            events.extend(step(self));
+        }
+        events
+    }
+
+    /// Plays rounds until the battle ends, giving up after `max_rounds`.
+    pub fn run(&mut self, max_rounds: usize) -> Option<BattleOutcome> {
+        for _ in 0..max_rounds {
+            if self.outcome().is_some() {
+                break;
+            }
+            self.round();
+        }
+        self.outcome()
+    }
+
+    fn scroll(&self, side: Side) -> &Vec<ItemComponents> {
+        match side {
+            Side::Player => &self.items,
+            Side::Enemy(index) => &self.enemies[index].items,
+        }
+    }
+
+    fn scroll_mut(&mut self, side: Side) -> (&mut Vec<ItemComponents>, &mut usize) {
+        match side {
+            Side::Player => (&mut self.items, &mut self.marker),
+            Side::Enemy(index) => {
+                let enemy = &mut self.enemies[index];
+                (&mut enemy.items, &mut enemy.marker)
+            }
+        }
+    }
+
+    fn hp(&self, side: Side) -> &Hp {
+        match side {
+            Side::Player => &self.player.hp,
+            Side::Enemy(index) => &self.enemies[index].hp,
+        }
+    }
+
+    fn hp_mut(&mut self, side: Side) -> &mut Hp {
+        match side {
+            Side::Player => &mut self.player.hp,
+            Side::Enemy(index) => &mut self.enemies[index].hp,
+        }
+    }
+
+    fn stats(&self, side: Side) -> &PlayerStats {
+        match side {
+            Side::Player => &self.player.stats,
+            Side::Enemy(index) => &self.enemies[index].stats,
+        }
+    }
+
+    fn stats_mut(&mut self, side: Side) -> &mut PlayerStats {
+        match side {
+            Side::Player => &mut self.player.stats,
+            Side::Enemy(index) => &mut self.enemies[index].stats,
+        }
+    }
+
+    fn statuses_mut(&mut self, side: Side) -> &mut StatusEffects {
+        match side {
+            Side::Player => &mut self.player.statuses,
+            Side::Enemy(index) => &mut self.enemies[index].statuses,
+        }
+    }
+
+    /// Drops the buffs `side` picked up during its previous turn.
+    fn expire_turn_buffs(&mut self, side: Side) {
+        for item in self.scroll_mut(side).0.iter_mut() {
+            item.expire_modifiers(ModifierScope::Turn);
+        }
+        self.hp_mut(side).expire_bonus(ModifierScope::Turn);
+    }
+
+    fn tick_statuses(&mut self, side: Side, events: &mut Vec<SimEvent>) {
+        let damage = self.statuses_mut(side).tick();
+        if damage > 0 {
+            self.hurt_self(side, damage, DamageKind::Status, events);
+        }
+    }
+
+    fn bleed(&mut self, side: Side, events: &mut Vec<SimEvent>) {
+        let bleed = self.statuses_mut(side).bleed();
+        if bleed > 0 {
+            self.hurt_self(side, bleed, DamageKind::Status, events);
+        }
+    }
+
+    /// Who damage from `side`'s items lands on.
+    fn damage_targets(&self, side: Side, spread: bool) -> Vec<Side> {
+        match side {
+            Side::Enemy(_) => vec![Side::Player],
+            Side::Player if spread => (0..self.enemies.len())
+                .filter(|&i| !self.enemies[i].hp.is_dead())
+                .map(Side::Enemy)
+                .collect(),
+            Side::Player => (self.target < self.enemies.len())
+                .then_some(Side::Enemy(self.target))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Damage `side` deals to itself, such as from its status effects.
+    fn hurt_self(&mut self, side: Side, amount: i32, kind: DamageKind, events: &mut Vec<SimEvent>) {
+        let event = DamageEvent {
+            source: side,
+            item: None,
+            ability: None,
+            target: side,
+            amount,
+            kind,
+        };
+        self.damage(event, events);
+    }
+
+    fn wear_off_sea_legs(&mut self, side: Side) {
+        let sea_legs = &mut self.stats_mut(side).sea_legs;
+        *sea_legs = (*sea_legs - 1).max(0);
+    }
+
+    /// Uses the item under `side`'s marker and moves the marker on.
+    fn take_turn(&mut self, side: Side, events: &mut Vec<SimEvent>) {
+        let (items, marker) = self.scroll_mut(side);
+        let index = *marker;
+        if let Some(item) = items.get_mut(index) {
+            let mut consumed = false;
+            if let Some(consumable) = item.consumable.as_mut() {
+                let before = consumable.0;
+                consumable.0 = (consumable.0 - 1).max(0);
+                consumed = consumable.0 == 0;
+                events.push(SimEvent::Effect(SimEffect {
+                    source: side,
+                    item: Some(index),
+                    ability: None,
+                    target: EffectTarget::Item(side, index),
+                    stat: EffectStat::Uses,
+                    before,
+                    after: consumable.0,
+                }));
+            }
+
+            self.use_item(side, index, events);
+
+            let (items, marker) = self.scroll_mut(side);
+            if consumed {
+                items.remove(index);
+                events.push(SimEvent::ItemConsumed(side, index));
+            } else {
+                *marker += 1;
+            }
+            let wrapped = !items.is_empty() && *marker >= items.len();
+            *marker = match items.len() {
+                0 => 0,
+                len => *marker % len,
+            };
+            if wrapped && side == Side::Player {
+                self.passes += 1;
+            }
+        }
+    }
+
+    fn targets(&mut self, side: Side, index: usize, target: &AbilityTarget) -> Vec<usize> {
+        let items = self.scroll(side);
+        let targets: Vec<usize> = target
+            .filter
+            .get_targets(index, items.len())
+            .into_iter()
+            .filter(|&i| items[i].matches(&target.attribute))
+            .collect();
+        match target.filter {
+            TargetFilter::Random(n) => targets.choose_multiple(&mut self.rng, n).copied().collect(),
+            _ => targets,
+        }
+    }
+
+    /// Whether the item at `index` gets to deal its damage. Firearms need a
+    /// loaded shot that doesn't misfire; an empty one clicks, or spends the
+    /// turn reloading if it can.
+    fn fire(&mut self, side: Side, index: usize, events: &mut Vec<SimEvent>) -> bool {
+        let Some(flintlock) = self.scroll(side)[index].flintlock.clone() else {
+            return true;
+        };
+        let ammo = |before: usize, after: usize| {
+            SimEvent::Effect(SimEffect {
+                source: side,
+                item: Some(index),
+                ability: None,
+                target: EffectTarget::Item(side, index),
+                stat: EffectStat::Ammo,
+                before: before as i32,
+                after: after as i32,
+            })
+        };
+        if flintlock.loaded() == 0 {
+            if flintlock.reloads() && self.reload(side, index) {
+                events.push(SimEvent::Reload(side));
+                let loaded = self.scroll(side)[index].flintlock.as_ref();
+                events.push(ammo(0, loaded.map_or(0, |f| f.loaded())));
+            } else {
+                events.push(SimEvent::Click(side));
+            }
+            return false;
+        }
+        if let Some(flintlock) = self.scroll_mut(side).0[index].flintlock.as_mut() {
+            let before = flintlock.loaded();
+            flintlock.fire();
+            events.push(ammo(before, flintlock.loaded()));
+        }
+        if flintlock.misfire() > 0 && self.rng.gen_range(0..100) < flintlock.misfire() {
+            events.push(SimEvent::Misfire(side));
+            return false;
+        }
+        true
+    }
+
+    /// Loads the firearm at `index` from the first neighbour carrying ammo
+    /// it takes.
+    fn reload(&mut self, side: Side, index: usize) -> bool {
+        let items = &mut self.scroll_mut(side).0;
+        match (
+            Self::reload_amount(items, index),
+            items[index].flintlock.as_mut(),
+        ) {
+            (Some(load_amount), Some(flintlock)) => {
+                flintlock.load(load_amount);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// How much the first neighbour carrying ammo the firearm at `index`
+    /// takes would load into it.
+    fn reload_amount(items: &[ItemComponents], index: usize) -> Option<usize> {
+        TargetFilter::Neighbours
+            .get_targets(index, items.len())
+            .into_iter()
+            .flat_map(|i| {
+                let item = &items[i];
+                let pellets = (item.pellets.as_ref()).map(|p| (p.tag(), p.load_amount));
+                let cannonball = (item.cannonball.as_ref()).map(|c| (c.tag(), c.load_amount));
+                [pellets, cannonball]
+            })
+            .flatten()
+            .find(|&(tag, _)| {
+                (items[index].flintlock.as_ref()).is_some_and(|flintlock| flintlock.can_load(tag))
+            })
+            .map(|(_, load_amount)| load_amount)
+    }
+
+    fn use_item(&mut self, side: Side, index: usize, events: &mut Vec<SimEvent>) {
+        let item = self.scroll(side)[index].clone();
+        let scope = item.scope.unwrap_or_default();
+
+        let effect = |ability, target, stat, before, after| {
+            SimEvent::Effect(SimEffect {
+                source: side,
+                item: Some(index),
+                ability,
+                target,
+                stat,
+                before,
+                after,
+            })
+        };
+
+        if let Some(vitality) = &item.vitality {
+            let before = self.hp(side).max;
+            self.hp_mut(side).max_increase(vitality.amount(), scope);
+            let after = self.hp(side).max;
+            let (ability, target) = (Some(AbilityKind::Vitality), EffectTarget::Side(side));
+            events.push(effect(ability, target, EffectStat::MaxHp, before, after));
+        }
+
+        if let Some(damage) = &item.damage {
+            if self.fire(side, index, events) {
+                for target in self.damage_targets(side, item.spread.is_some()) {
+                    let event = DamageEvent {
+                        source: side,
+                        item: Some(index),
+                        ability: Some(AbilityKind::Damage),
+                        target,
+                        amount: damage.amount(),
+                        kind: DamageKind::Attack,
+                    };
+                    self.damage(event, events);
+                }
+            }
+        }
+
+        let statuses: Vec<(AbilityKind, StatusEffect)> = [
+            (item.poison.as_ref()).map(|poison| (AbilityKind::Poison, poison.effect())),
+            (item.bleed.as_ref()).map(|bleed| (AbilityKind::Bleed, bleed.effect())),
+            (item.burn.as_ref()).map(|burn| (AbilityKind::Burn, burn.effect())),
+            (item.stun.as_ref()).map(|stun| (AbilityKind::Stun, stun.effect())),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !statuses.is_empty() {
+            for target in self.damage_targets(side, item.spread.is_some()) {
+                for &(ability, status) in statuses.iter() {
+                    let stacks = |sim: &mut Self| {
+                        let statuses = sim.statuses_mut(target);
+                        statuses.get(status.kind).map_or(0, |s| s.stacks)
+                    };
+                    let before = stacks(self);
+                    self.statuses_mut(target).apply(status);
+                    let after = stacks(self);
+                    let stat = EffectStat::Status(status.kind);
+                    let target = EffectTarget::Side(target);
+                    events.push(effect(Some(ability), target, stat, before, after));
+                }
+            }
+        }
+
+        if let Some(hearties) = &item.hearties {
+            let event = HealEvent {
+                source: side,
+                item: Some(index),
+                ability: Some(AbilityKind::Hearties),
+                target: side,
+                amount: hearties.amount(),
+            };
+            self.heal(event, events);
+        }
+
+        if let Some(cursed) = &item.cursed {
+            let event = DamageEvent {
+                source: side,
+                item: Some(index),
+                ability: Some(AbilityKind::Cursed),
+                target: side,
+                amount: cursed.amount(),
+                kind: DamageKind::Cursed,
+            };
+            self.damage(event, events);
+        }
+
+        for buff in item.buff_effects() {
+            for i in self.targets(side, index, &buff.target) {
+                let Some(target) = self.scroll_mut(side).0[i].ability_mut(buff.ability) else {
+                    continue;
+                };
+                let before = target.amount();
+                target.modifier_mut().add(scope, buff.amount);
+                let after = target.amount();
+                let stat = EffectStat::Ability(buff.ability);
+                let target = EffectTarget::Item(side, i);
+                events.push(effect(Some(buff.source), target, stat, before, after));
+            }
+        }
+
+        if let Some(sea_legs) = &item.sea_legs {
+            let before = self.stats(side).sea_legs;
+            self.stats_mut(side).sea_legs += sea_legs.amount();
+            let after = self.stats(side).sea_legs;
+            let (ability, target) = (Some(AbilityKind::SeaLegs), EffectTarget::Side(side));
+            events.push(effect(ability, target, EffectStat::SeaLegs, before, after));
+        }
+
+        let pellets = (item.pellets.as_ref()).map(|p| (p.tag(), p.load_amount, &p.target));
+        let cannonball = (item.cannonball.as_ref()).map(|c| (c.tag(), c.load_amount, &c.target));
+        for (ammo, load_amount, target) in [pellets, cannonball].into_iter().flatten() {
+            for i in self.targets(side, index, target) {
+                if let Some(flintlock) = self.scroll_mut(side).0[i].flintlock.as_mut() {
+                    if flintlock.can_load(ammo) {
+                        let before = flintlock.loaded() as i32;
+                        flintlock.load(load_amount);
+                        let after = flintlock.loaded() as i32;
+                        let target = EffectTarget::Item(side, i);
+                        events.push(effect(None, target, EffectStat::Ammo, before, after));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::items::{
+        abilities::{Bleed, Heave, Poison, SeaLegs},
+        attributes::{AmmoKind, Flintlock, Pellets},
+        Consumable,
+    };
+
+    use crate::enemy::EnrageRule;
+
+    use super::*;
+
+    fn item(build: impl FnOnce(&mut ItemComponents)) -> ItemComponents {
+        let mut item = ItemComponents::new(ItemType::new("Test"));
+        build(&mut item);
+        item
+    }
+
+    fn sword(damage: i32) -> ItemComponents {
+        item(|item| item.damage = Some(Damage::new(damage)))
+    }
+
+    fn heave(amount: i32, filter: TargetFilter) -> ItemComponents {
+        item(|item| {
+            item.heave = Some(Heave::new(
+                amount,
+                AbilityTarget::with_all_attributes(filter),
+            ))
+        })
+    }
+
+    fn dummy(hp: i32) -> SimEnemy {
+        SimEnemy::new(Hp::new(hp), Damage::new(0), EnemyBehaviour::Strike, vec![])
+    }
+
+    fn sim(items: Vec<ItemComponents>, enemies: Vec<SimEnemy>) -> BattleSim {
+        BattleSim::new(items, SimPlayer::new(Hp::new(20)), enemies)
+    }
+
+    #[test]
+    fn player_uses_items_in_order_and_wraps() {
+        let mut sim = sim(vec![sword(2), sword(3)], vec![dummy(20)]);
+        sim.round();
+        assert_eq!(sim.enemies[0].hp.current, 18);
+        assert_eq!(sim.marker, 1);
+        sim.round();
+        assert_eq!(sim.enemies[0].hp.current, 15);
+        assert_eq!((sim.marker, sim.passes), (0, 1));
+    }
+
+    #[test]
+    fn dead_targets_move_on_to_the_next_enemy_standing() {
+        let mut sim = sim(vec![sword(5)], vec![dummy(5), dummy(5), dummy(5)]);
+        sim.enemies[1].hp.current = 0;
+        sim.player_turn();
+        assert_eq!(sim.target, 2);
+        sim.cycle_target();
+        assert_eq!(sim.target, 2);
+    }
+
+    #[test]
+    fn player_turn_without_enemies_does_not_panic() {
+        let mut sim = sim(vec![sword(5)], vec![]);
+        sim.player_turn();
+        assert_eq!(sim.outcome(), Some(BattleOutcome::Won));
+    }
+
+    #[test]
+    fn heave_next_buffs_only_the_following_items() {
+        let items = vec![heave(2, TargetFilter::Next(1)), sword(3), sword(3)];
+        let mut sim = sim(items, vec![dummy(20)]);
+        sim.player_turn();
+        assert_eq!(sim.items[1].damage.as_ref().unwrap().amount(), 5);
+        assert_eq!(sim.items[2].damage.as_ref().unwrap().amount(), 3);
+        sim.player_turn();
+        assert_eq!(sim.enemies[0].hp.current, 15);
+    }
+
+    #[test]
+    fn turn_buffs_wear_off_when_the_next_turn_begins() {
+        let mut buffer = heave(2, TargetFilter::Next(1));
+        buffer.scope = Some(ModifierScope::Turn);
+        let mut sim = sim(vec![buffer, sword(3)], vec![dummy(20)]);
+        sim.player_turn();
+        assert_eq!(sim.items[1].damage.as_ref().unwrap().amount(), 5);
+        sim.begin_player_turn();
+        assert_eq!(sim.items[1].damage.as_ref().unwrap().amount(), 3);
+    }
+
+    #[test]
+    fn heave_neighbours_buffs_both_sides() {
+        let items = vec![
+            sword(1),
+            heave(1, TargetFilter::Neighbours),
+            sword(1),
+            sword(1),
+        ];
+        let mut sim = sim(items, vec![dummy(20)]);
+        sim.marker = 1;
+        sim.player_turn();
+        let damage: Vec<i32> = (sim.items.iter())
+            .map(|item| item.damage.as_ref().map_or(0, |d| d.amount()))
+            .collect();
+        assert_eq!(damage, [2, 0, 2, 1]);
+        let modifier = sim.items[0].damage.as_ref().unwrap().modifier;
+        assert_eq!(modifier.get(ModifierScope::Battle), 1);
+    }
+
+    #[test]
+    fn random_targets_follow_the_seed() {
+        let buffer = heave(1, TargetFilter::Random(2));
+        let items = vec![buffer, sword(1), sword(1), sword(1), sword(1)];
+        let picks = |seed| {
+            let mut sim = sim(items.clone(), vec![dummy(20)]).with_seed(seed);
+            sim.player_turn();
+            let damage = |item: &ItemComponents| item.damage.as_ref().map(|d| d.amount());
+            (0..sim.items.len())
+                .filter(|&i| damage(&sim.items[i]) == Some(2))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(picks(3).len(), 2);
+        assert_eq!(picks(3), picks(3));
+    }
+
+    #[test]
+    fn pellets_load_a_flintlock_that_then_fires() {
+        let items = vec![
+            pellets(TargetFilter::Next(1)),
+            pistol(Flintlock::empty(AmmoKind::Shot, 2)),
+        ];
+        let mut sim = sim(items, vec![dummy(20)]);
+
+        sim.player_turn();
+        assert_eq!(sim.items[1].flintlock.as_ref().unwrap().loaded(), 1);
+        sim.player_turn();
+        assert_eq!(sim.items[1].flintlock.as_ref().unwrap().loaded(), 0);
+        assert_eq!(sim.enemies[0].hp.current, 16);
+    }
+
+    fn pistol(flintlock: Flintlock) -> ItemComponents {
+        let mut pistol = sword(4);
+        pistol.flintlock = Some(flintlock);
+        pistol
+    }
+
+    fn pellets(filter: TargetFilter) -> ItemComponents {
+        item(|item| {
+            item.pellets = Some(Pellets {
+                load_amount: 1,
+                target: AbilityTarget::with_all_attributes(filter),
+            })
+        })
+    }
+
+    fn ammo_changes(events: &[SimEvent]) -> Vec<(i32, i32)> {
+        (events.iter())
+            .filter_map(|event| match event {
+                SimEvent::Effect(effect) if effect.stat == EffectStat::Ammo => {
+                    Some((effect.before, effect.after))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn empty_flintlocks_click() {
+        let mut sim = sim(
+            vec![pistol(Flintlock::empty(AmmoKind::Shot, 1))],
+            vec![dummy(20)],
+        );
+        let events = sim.player_turn();
+        assert!(events.contains(&SimEvent::Click(Side::Player)));
+        assert_eq!(sim.enemies[0].hp.current, 20);
+    }
+
+    #[test]
+    fn reloading_flintlocks_spend_the_turn_loading_from_neighbours() {
+        let reloads = Flintlock::empty(AmmoKind::Shot, 2).with_reloads(true);
+        let items = vec![pistol(reloads), pellets(TargetFilter::SelfOnly)];
+        let mut sim = sim(items, vec![dummy(20)]);
+        let events = sim.player_turn();
+        assert!(events.contains(&SimEvent::Reload(Side::Player)));
+        assert_eq!(ammo_changes(&events), [(0, 1)]);
+        assert_eq!(sim.enemies[0].hp.current, 20);
+
+        sim.marker = 0;
+        let events = sim.player_turn();
+        assert_eq!(ammo_changes(&events), [(1, 0)]);
+        assert_eq!(sim.enemies[0].hp.current, 16);
+    }
+
+    #[test]
+    fn misfires_spend_the_shot_without_hitting() {
+        let mut flintlock = Flintlock::empty(AmmoKind::Shot, 2).with_misfire(100);
+        flintlock.load(2);
+        let mut sim = sim(vec![pistol(flintlock)], vec![dummy(20)]);
+        let events = sim.player_turn();
+        assert!(events.contains(&SimEvent::Misfire(Side::Player)));
+        assert_eq!(ammo_changes(&events), [(2, 1)]);
+        assert_eq!(sim.enemies[0].hp.current, 20);
+    }
+
+    #[test]
+    fn cursed_items_hurt_their_owner_through_sea_legs() {
+        let mut blade = sword(5);
+        blade.cursed = Some(Cursed::new(2));
+        let mut sim = sim(vec![blade], vec![dummy(20)]);
+        sim.player.stats.sea_legs = 5;
+        sim.player_turn();
+        assert_eq!(sim.enemies[0].hp.current, 15);
+        assert_eq!(sim.player.hp.current, 18);
+    }
+
+    #[test]
+    fn stunned_sides_skip_their_turn_and_say_so() {
+        let mut sim = sim(vec![sword(2)], vec![dummy(20)]);
+        let stun = StatusEffect {
+            kind: StatusKind::Stun,
+            stacks: 1,
+            duration: 1,
+        };
+        sim.player.statuses.apply(stun);
+        sim.enemies[0].statuses.apply(stun);
+        assert!(sim.player_turn().contains(&SimEvent::Stunned(Side::Player)));
+        assert!(sim
+            .enemy_turn()
+            .contains(&SimEvent::Stunned(Side::Enemy(0))));
+        assert_eq!((sim.enemies[0].hp.current, sim.marker), (20, 0));
+        assert!(!sim.player_turn().contains(&SimEvent::Stunned(Side::Player)));
+        assert_eq!(sim.enemies[0].hp.current, 18);
+    }
+
+    #[test]
+    fn statuses_tick_at_the_start_of_a_turn_and_bleed_on_acting() {
+        let mut sim = sim(vec![sword(1)], vec![dummy(20)]);
+        let mut poison = sword(0);
+        poison.poison = Some(Poison::new(2, 2));
+        poison.bleed = Some(Bleed::new(1, 2));
+        sim.enemies[0].items = vec![poison];
+        sim.enemy_turn();
+        sim.begin_player_turn();
+        assert_eq!(sim.player.hp.current, 18);
+        sim.player_turn();
+        assert_eq!(sim.player.hp.current, 17);
+    }
+
+    fn effects(events: &[SimEvent]) -> Vec<SimEffect> {
+        (events.iter())
+            .filter_map(|event| match event {
+                SimEvent::Effect(effect) => Some(*effect),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hits_report_the_item_ability_and_target() {
+        let mut sim = sim(vec![sword(3)], vec![dummy(5), dummy(5)]);
+        sim.target = 1;
+        let events = sim.player_turn();
+        assert_eq!(
+            effects(&events),
+            [SimEffect {
+                source: Side::Player,
+                item: Some(0),
+                ability: Some(AbilityKind::Damage),
+                target: EffectTarget::Side(Side::Enemy(1)),
+                stat: EffectStat::Hp,
+                before: 5,
+                after: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn buffs_report_the_item_they_landed_on() {
+        let mut sim = sim(
+            vec![heave(2, TargetFilter::Next(1)), sword(3)],
+            vec![dummy(5)],
+        );
+        let events = sim.player_turn();
+        let effect = effects(&events)[0];
+        assert_eq!(effect.ability, Some(AbilityKind::Heave));
+        assert_eq!(effect.target, EffectTarget::Item(Side::Player, 1));
+        assert_eq!(effect.stat, EffectStat::Ability(AbilityKind::Damage));
+        assert_eq!((effect.before, effect.after), (3, 5));
+    }
+
+    #[test]
+    fn consumables_report_their_uses_before_being_used_up() {
+        let mut grog = item(|item| item.sea_legs = Some(SeaLegs::new(2)));
+        grog.consumable = Some(Consumable(1));
+        let mut sim = sim(vec![grog], vec![dummy(5)]);
+        let events = sim.player_turn();
+        let stats: Vec<EffectStat> = effects(&events).iter().map(|e| e.stat).collect();
+        assert_eq!(stats, [EffectStat::Uses, EffectStat::SeaLegs]);
+        assert_eq!(
+            events.last(),
+            Some(&SimEvent::ItemConsumed(Side::Player, 0))
+        );
+        assert!(sim.items.is_empty());
+    }
+
+    #[test]
+    fn enemies_hit_with_flat_damage_or_their_own_scroll() {
+        let brute = SimEnemy::new(Hp::new(10), Damage::new(3), EnemyBehaviour::Strike, vec![]);
+        let mut grog = item(|item| item.sea_legs = Some(SeaLegs::new(2)));
+        grog.consumable = Some(Consumable(1));
+        let sailor = SimEnemy::new(
+            Hp::new(10),
+            Damage::new(0),
+            EnemyBehaviour::Strike,
+            vec![grog, sword(4)],
+        );
+        let mut sim = sim(vec![], vec![brute, sailor]);
+
+        sim.enemy_turn();
+        assert_eq!(sim.player.hp.current, 17);
+        assert_eq!(sim.enemies[1].stats.sea_legs, 2);
+        assert_eq!((sim.enemies[1].items.len(), sim.enemies[1].marker), (1, 0));
+        sim.enemy_turn();
+        assert_eq!(sim.player.hp.current, 10);
+    }
+
+    fn enraging(rule: EnrageRule, items: Vec<ItemComponents>) -> SimEnemy {
+        SimEnemy::new(Hp::new(100), Damage::new(1), EnemyBehaviour::Strike, items)
+            .with_enrage(Some(Enrage { rule, damage: 1 }))
+    }
+
+    #[test]
+    fn enemies_enrage_every_turn_past_their_limit() {
+        let enemy = enraging(EnrageRule::AfterTurns(2), vec![]);
+        let mut sim = sim(vec![], vec![enemy]);
+        sim.player.hp = Hp::new(100);
+        sim.round();
+        sim.round();
+        assert_eq!(sim.enemies[0].damage.amount(), 1);
+        sim.round();
+        sim.round();
+        assert_eq!(sim.enemies[0].damage.amount(), 3);
+        assert_eq!(sim.player.hp.current, 100 - 1 - 1 - 2 - 3);
+    }
+
+    #[test]
+    fn per_pass_enrage_waits_for_the_players_scroll_to_wrap() {
+        let enemy = enraging(EnrageRule::PerPass, vec![]);
+        let mut sim = sim(vec![sword(0), sword(0)], vec![enemy]);
+        sim.round();
+        assert_eq!((sim.passes, sim.enemies[0].damage.amount()), (0, 1));
+        sim.round();
+        assert_eq!((sim.passes, sim.enemies[0].damage.amount()), (1, 2));
+        sim.round();
+        assert_eq!(sim.enemies[0].damage.amount(), 2);
+    }
+
+    #[test]
+    fn enrage_also_sharpens_the_enemys_weapons() {
+        let enemy = enraging(
+            EnrageRule::AfterTurns(0),
+            vec![sword(2), heave(1, TargetFilter::All)],
+        );
+        let mut sim = sim(vec![], vec![enemy]);
+        sim.round();
+        let enemy = &sim.enemies[0];
+        assert_eq!(enemy.items[0].damage.as_ref().unwrap().amount(), 3);
+        assert!(enemy.items[1].damage.is_none());
+        assert_eq!(sim.player.hp.current, 17);
+    }
+
+    #[test]
+    fn curse_item_mechanic_curses_a_player_item_for_the_battle() {
+        let boss = dummy(20).with_mechanics(vec![BossMechanic::CurseItem(2)]);
+        let mut sim = sim(vec![sword(1)], vec![boss]);
+        sim.enemy_turn();
+        assert_eq!(sim.items[0].cursed.as_ref().map(|c| c.amount()), Some(2));
+        sim.player_turn();
+        assert_eq!(sim.player.hp.current, 18);
+
+        sim.items[0].expire_modifiers(ModifierScope::Battle);
+        assert!(sim.items[0].cursed.is_none());
+    }
+
+    #[test]
+    fn gain_armor_mechanic_triggers_every_few_turns() {
+        let mechanic = BossMechanic::GainArmor {
+            every: 2,
+            amount: 3,
+        };
+        let mut sim = sim(vec![], vec![dummy(20).with_mechanics(vec![mechanic])]);
+        let armor: Vec<i32> = (0..4)
+            .map(|_| {
+                sim.round();
+                sim.enemies[0].stats.armor
+            })
+            .collect();
+        assert_eq!(armor, [0, 3, 3, 6]);
+    }
+
+    #[test]
+    fn armored_elites_add_to_their_armor() {
+        let enemy = dummy(20).with_armor(1);
+        let enemy = enemy.with_affixes(vec![EnemyAffix::Armored(2)]);
+        assert_eq!(enemy.stats.armor, 3);
+        let mut sim = sim(vec![sword(5)], vec![enemy]);
+        sim.player_turn();
+        assert_eq!(sim.enemies[0].hp.current, 18);
+    }
+
+    #[test]
+    fn swift_elites_act_twice_a_turn() {
+        let bare = SimEnemy::new(Hp::new(20), Damage::new(2), EnemyBehaviour::Strike, vec![])
+            .with_affixes(vec![EnemyAffix::Swift]);
+        let items = vec![sword(1), sword(3), sword(5)];
+        let armed = SimEnemy::new(Hp::new(20), Damage::new(0), EnemyBehaviour::Strike, items)
+            .with_affixes(vec![EnemyAffix::Swift]);
+        let mut sim = sim(vec![], vec![bare, armed]);
+        let events = sim.enemy_turn();
+        let attacks = events
+            .iter()
+            .filter(|event| matches!(event, SimEvent::EnemyAttack(_)))
+            .count();
+        assert_eq!(attacks, 4);
+        assert_eq!(sim.player.hp.current, 20 - 2 - 2 - 1 - 3);
+        assert_eq!(sim.enemies[1].marker, 2);
+    }
+
+    #[test]
+    fn intents_follow_the_enemy_marker() {
+        let grog = item(|item| {
+            item.sea_legs = Some(SeaLegs::new(2));
+            item.consumable = Some(Consumable(1));
+        });
+        let poison = item(|item| item.poison = Some(Poison::new(1, 3)));
+        let enemy = SimEnemy::new(
+            Hp::new(20),
+            Damage::new(0),
+            EnemyBehaviour::Strike,
+            vec![grog, sword(3), poison],
+        );
+        let mut sim = sim(vec![], vec![enemy]);
+        assert_eq!(sim.enemy_intents(0), [Intent::Defend(2)]);
+        sim.enemy_turn();
+        assert_eq!(sim.enemy_intents(0), [Intent::Attack(3)]);
+        sim.enemy_turn();
+        assert_eq!(sim.enemy_intents(0), [Intent::Debuff(1)]);
+    }
+
+    #[test]
+    fn swift_intents_preview_both_actions() {
+        let grog = item(|item| {
+            item.sea_legs = Some(SeaLegs::new(2));
+            item.consumable = Some(Consumable(1));
+        });
+        let buff = heave(1, TargetFilter::Next(1));
+        let armed = SimEnemy::new(
+            Hp::new(20),
+            Damage::new(0),
+            EnemyBehaviour::Strike,
+            vec![grog, sword(3), buff],
+        )
+        .with_affixes(vec![EnemyAffix::Swift]);
+        let bare = SimEnemy::new(Hp::new(20), Damage::new(2), EnemyBehaviour::Strike, vec![])
+            .with_affixes(vec![EnemyAffix::Swift]);
+        let mut sim = sim(vec![], vec![armed, bare]);
+        assert_eq!(sim.enemy_intents(0), [Intent::Defend(2), Intent::Attack(3)]);
+        assert_eq!(sim.enemy_intents(1), [Intent::Attack(2), Intent::Attack(2)]);
+        sim.enemy_turn();
+        assert_eq!(sim.enemy_intents(0), [Intent::Buff(1), Intent::Attack(3)]);
+    }
+
+    #[test]
+    fn unlisted_items_show_an_unknown_intent() {
+        let enemy = SimEnemy::new(
+            Hp::new(20),
+            Damage::new(0),
+            EnemyBehaviour::Strike,
+            vec![item(|_| {})],
+        );
+        let mut sim = sim(vec![], vec![enemy]);
+        assert_eq!(sim.enemy_intents(0), [Intent::Unknown]);
+        sim.enemies[0].hp.current = 0;
+        assert_eq!(sim.enemy_intents(0), []);
+    }
+
+    #[test]
+    fn empty_flintlocks_do_not_show_an_attack() {
+        let enemy =
+            |items| SimEnemy::new(Hp::new(20), Damage::new(0), EnemyBehaviour::Strike, items);
+        let empty = Flintlock::empty(AmmoKind::Shot, 1);
+        let mut loaded = empty.clone();
+        loaded.load(1);
+        let reloading = vec![
+            pistol(empty.clone().with_reloads(true)),
+            pellets(TargetFilter::SelfOnly),
+        ];
+        let swift = enemy(vec![pistol(loaded.clone())]).with_affixes(vec![EnemyAffix::Swift]);
+        let sim = sim(
+            vec![],
+            vec![
+                enemy(vec![pistol(empty)]),
+                enemy(reloading),
+                enemy(vec![pistol(loaded)]),
+                swift,
+            ],
+        );
+        assert_eq!(sim.enemy_intents(0), [Intent::Unknown]);
+        assert_eq!(sim.enemy_intents(1), [Intent::Buff(1)]);
+        assert_eq!(sim.enemy_intents(2), [Intent::Attack(4)]);
+        assert_eq!(sim.enemy_intents(3), [Intent::Attack(4), Intent::Unknown]);
+    }
+}
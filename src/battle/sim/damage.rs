@@ -0,0 +1,241 @@
+//! Every change to hp in a battle goes through `BattleSim::damage` or
+//! `BattleSim::heal`. Damage runs through the stages below in order:
+//! modifiers, mitigation, application, then on-hit reactions. New mechanics
+//! hook in by adding a function to the stage they belong to.
+
+use crate::{
+    enemy::{EnemyAffix, EnemyBehaviour},
+    items::abilities::AbilityKind,
+};
+
+use super::{BattleSim, EffectStat, EffectTarget, Side, SimEffect, SimEvent};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageKind {
+    /// A weapon or an enemy's blow. The only kind that can be mitigated.
+    Attack,
+    /// Self-inflicted, such as `Cursed`.
+    Cursed,
+    /// Ticking status effects.
+    Status,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DamageEvent {
+    pub source: Side,
+    /// Index of the item dealing the damage in `source`'s scroll.
+    pub item: Option<usize>,
+    pub ability: Option<AbilityKind>,
+    pub target: Side,
+    pub amount: i32,
+    pub kind: DamageKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HealEvent {
+    pub source: Side,
+    pub item: Option<usize>,
+    pub ability: Option<AbilityKind>,
+    pub target: Side,
+    pub amount: i32,
+}
+
+type DamageHook = fn(&BattleSim, &mut DamageEvent);
+type DamageReaction = fn(&mut BattleSim, &DamageEvent, &mut Vec<SimEvent>);
+type HealHook = fn(&BattleSim, &mut HealEvent);
+
+const DAMAGE_MODIFIERS: &[DamageHook] = &[cursed_aura];
+const MITIGATION: &[DamageHook] = &[sea_legs, armor];
+const REACTIONS: &[DamageReaction] = &[leech];
+const HEAL_MODIFIERS: &[HealHook] = &[];
+
+impl BattleSim {
+    pub fn damage(&mut self, mut event: DamageEvent, events: &mut Vec<SimEvent>) {
+        for hook in DAMAGE_MODIFIERS.iter().chain(MITIGATION) {
+            hook(self, &mut event);
+        }
+        event.amount = event.amount.max(0);
+
+        events.push(match event.target {
+            Side::Player => SimEvent::PlayerHurt(event.amount),
+            Side::Enemy(index) => SimEvent::EnemyHurt(index, event.amount),
+        });
+        let before = self.hp(event.target).current;
+        self.hp_mut(event.target).decrease(event.amount);
+        events.push(SimEvent::Effect(SimEffect {
+            source: event.source,
+            item: event.item,
+            ability: event.ability,
+            target: EffectTarget::Side(event.target),
+            stat: EffectStat::Hp,
+            before,
+            after: self.hp(event.target).current,
+        }));
+
+        for reaction in REACTIONS {
+            reaction(self, &event, events);
+        }
+    }
+
+    pub fn heal(&mut self, mut event: HealEvent, events: &mut Vec<SimEvent>) {
+        for hook in HEAL_MODIFIERS {
+            hook(self, &mut event);
+        }
+        event.amount = event.amount.max(0);
+
+        events.push(match event.target {
+            Side::Player => SimEvent::PlayerHeal(event.amount),
+            Side::Enemy(index) => SimEvent::EnemyHeal(index, event.amount),
+        });
+        let before = self.hp(event.target).current;
+        self.hp_mut(event.target).increase(event.amount);
+        events.push(SimEvent::Effect(SimEffect {
+            source: event.source,
+            item: event.item,
+            ability: event.ability,
+            target: EffectTarget::Side(event.target),
+            stat: EffectStat::Hp,
+            before,
+            after: self.hp(event.target).current,
+        }));
+    }
+}
+
+/// Standing enemies with a cursed aura double the player's curses.
+fn cursed_aura(sim: &BattleSim, event: &mut DamageEvent) {
+    let aura = sim
+        .enemies
+        .iter()
+        .any(|enemy| !enemy.hp.is_dead() && enemy.has_affix(EnemyAffix::CursedAura));
+    if aura && event.kind == DamageKind::Cursed && event.target == Side::Player {
+        event.amount *= 2;
+    }
+}
+
+fn sea_legs(sim: &BattleSim, event: &mut DamageEvent) {
+    if event.kind == DamageKind::Attack {
+        event.amount -= sim.stats(event.target).sea_legs;
+    }
+}
+
+fn armor(sim: &BattleSim, event: &mut DamageEvent) {
+    if event.kind == DamageKind::Attack {
+        event.amount -= sim.stats(event.target).armor;
+    }
+}
+
+/// Leeching and vampiric enemies heal by the damage their attacks deal.
+fn leech(sim: &mut BattleSim, event: &DamageEvent, events: &mut Vec<SimEvent>) {
+    let Side::Enemy(index) = event.source else {
+        return;
+    };
+    let enemy = &sim.enemies[index];
+    let leeches =
+        matches!(enemy.behaviour, EnemyBehaviour::Leech) || enemy.has_affix(EnemyAffix::Vampiric);
+    if leeches && event.kind == DamageKind::Attack && event.amount > 0 {
+        sim.heal(
+            HealEvent {
+                source: event.source,
+                item: event.item,
+                ability: None,
+                target: event.source,
+                amount: event.amount,
+            },
+            events,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{common::Hp, items::abilities::Damage};
+
+    use super::super::{SimEnemy, SimPlayer};
+    use super::*;
+
+    fn sim(enemy: SimEnemy) -> BattleSim {
+        BattleSim::new(vec![], SimPlayer::new(Hp::new(20)), vec![enemy])
+    }
+
+    fn enemy(behaviour: EnemyBehaviour) -> SimEnemy {
+        SimEnemy::new(Hp::new(20), Damage::new(0), behaviour, vec![])
+    }
+
+    fn hit(target: Side, amount: i32, kind: DamageKind) -> DamageEvent {
+        let source = match target {
+            Side::Player => Side::Enemy(0),
+            Side::Enemy(_) => Side::Player,
+        };
+        DamageEvent {
+            source,
+            item: None,
+            ability: None,
+            target,
+            amount,
+            kind,
+        }
+    }
+
+    #[test]
+    fn sea_legs_and_armor_both_mitigate_attacks() {
+        let mut sim = sim(enemy(EnemyBehaviour::Strike).with_armor(1));
+        sim.enemies[0].stats.sea_legs = 2;
+        sim.damage(hit(Side::Enemy(0), 5, DamageKind::Attack), &mut vec![]);
+        assert_eq!(sim.enemies[0].hp.current, 18);
+    }
+
+    #[test]
+    fn only_attacks_are_mitigated() {
+        let mut sim = sim(enemy(EnemyBehaviour::Strike));
+        sim.player.stats.sea_legs = 3;
+        sim.player.stats.armor = 3;
+        sim.damage(hit(Side::Player, 2, DamageKind::Cursed), &mut vec![]);
+        sim.damage(hit(Side::Player, 1, DamageKind::Status), &mut vec![]);
+        assert_eq!(sim.player.hp.current, 17);
+    }
+
+    #[test]
+    fn mitigated_damage_is_clamped_at_zero() {
+        let mut sim = sim(enemy(EnemyBehaviour::Strike).with_armor(5));
+        let mut events = vec![];
+        sim.damage(hit(Side::Enemy(0), 2, DamageKind::Attack), &mut events);
+        assert_eq!(sim.enemies[0].hp.current, 20);
+        assert_eq!(events[0], SimEvent::EnemyHurt(0, 0));
+    }
+
+    #[test]
+    fn leech_heals_by_the_damage_left_after_mitigation() {
+        let mut sim = sim(enemy(EnemyBehaviour::Leech));
+        sim.enemies[0].hp.current = 10;
+        sim.player.stats.armor = 1;
+        let mut events = vec![];
+        sim.damage(hit(Side::Player, 4, DamageKind::Attack), &mut events);
+        assert_eq!(sim.enemies[0].hp.current, 13);
+        assert!(events.contains(&SimEvent::EnemyHeal(0, 3)));
+
+        sim.player.stats.armor = 5;
+        sim.damage(hit(Side::Player, 4, DamageKind::Attack), &mut vec![]);
+        assert_eq!(sim.enemies[0].hp.current, 13);
+    }
+
+    #[test]
+    fn cursed_aura_doubles_the_players_curses_while_standing() {
+        let aura = enemy(EnemyBehaviour::Strike).with_affixes(vec![EnemyAffix::CursedAura]);
+        let mut sim = sim(aura);
+        sim.damage(hit(Side::Player, 2, DamageKind::Cursed), &mut vec![]);
+        sim.damage(hit(Side::Player, 2, DamageKind::Attack), &mut vec![]);
+        assert_eq!(sim.player.hp.current, 14);
+        sim.enemies[0].hp.current = 0;
+        sim.damage(hit(Side::Player, 2, DamageKind::Cursed), &mut vec![]);
+        assert_eq!(sim.player.hp.current, 12);
+    }
+
+    #[test]
+    fn vampiric_enemies_leech_like_leeches() {
+        let vampire = enemy(EnemyBehaviour::Strike).with_affixes(vec![EnemyAffix::Vampiric]);
+        let mut sim = sim(vampire);
+        sim.enemies[0].hp.current = 10;
+        sim.damage(hit(Side::Player, 4, DamageKind::Attack), &mut vec![]);
+        assert_eq!(sim.enemies[0].hp.current, 14);
+    }
+}
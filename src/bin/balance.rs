@@ -0,0 +1,71 @@
+use std::{fs, path::Path, process};
+
+use captains_gamble::{
+    assets::load_ron,
+    balance::{simulate_run, BalanceReport, BotPolicy},
+    common::arg_value,
+    enemy::EnemyDefinitions,
+    items::definitions::ItemDefinitions,
+    rng::random_seed,
+};
+
+const DEFAULT_RUNS: usize = 1000;
+const DEFAULT_MAX_WINS: usize = 100;
+const ITEMS_PATH: &str = "assets/data/base.items.ron";
+const ENEMIES_PATH: &str = "assets/data/base.enemies.ron";
+
+fn parsed_arg<T: std::str::FromStr>(flag: &str, default: T) -> T {
+    arg_value(flag)
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+fn exit_with(message: String) -> ! {
+    eprintln!("{message}");
+    process::exit(1);
+}
+
+fn main() {
+    let runs = parsed_arg("--runs", DEFAULT_RUNS);
+    let max_wins = parsed_arg("--max-wins", DEFAULT_MAX_WINS);
+    let seed = parsed_arg("--seed", random_seed());
+    let policy_name = arg_value("--policy").unwrap_or_else(|| "greedy".to_string());
+    let policy = BotPolicy::from_name(&policy_name)
+        .unwrap_or_else(|| exit_with(format!("Unknown policy {policy_name}")));
+
+    let items_path = arg_value("--items").unwrap_or_else(|| ITEMS_PATH.to_string());
+    let item_definitions: ItemDefinitions = load_ron(&items_path)
+        .unwrap_or_else(|err| exit_with(format!("Could not load {items_path}: {err}")));
+    let enemies_path = arg_value("--enemies").unwrap_or_else(|| ENEMIES_PATH.to_string());
+    let enemy_definitions: EnemyDefinitions = load_ron(&enemies_path)
+        .unwrap_or_else(|err| exit_with(format!("Could not load {enemies_path}: {err}")));
+
+    let results: Vec<_> = (0..runs as u64)
+        .map(|run| {
+            simulate_run(
+                &item_definitions,
+                &enemy_definitions,
+                policy,
+                seed.wrapping_add(run),
+                max_wins,
+            )
+        })
+        .collect();
+    let report = BalanceReport::new(policy, &results, &item_definitions);
+
+    if let Some(dir) = arg_value("--csv") {
+        let dir = Path::new(&dir);
+        let written = fs::create_dir_all(dir)
+            .and_then(|_| fs::write(dir.join("summary.csv"), report.summary_csv()))
+            .and_then(|_| fs::write(dir.join("battle_wins.csv"), report.battle_wins_csv()))
+            .and_then(|_| fs::write(dir.join("items.csv"), report.items_csv()));
+        if let Err(err) = written {
+            exit_with(format!("Could not write CSV to {}: {err}", dir.display()));
+        }
+    }
+
+    match serde_json::to_string_pretty(&report) {
+        Ok(json) => println!("{json}"),
+        Err(err) => exit_with(format!("Could not serialize report: {err}")),
+    }
+}
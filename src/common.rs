@@ -1,8 +1,13 @@
 use core::fmt;
+use std::env;
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::tooltip::{TooltipComponent, TooltipSection, TooltipSectionIndex};
+use crate::{
+    items::abilities::{AbilityModifier, ModifierScope},
+    tooltip::{TooltipComponent, TooltipSection, TooltipSectionIndex},
+};
 
 #[derive(Component, Clone, Debug)]
 pub struct Name(pub String);
@@ -13,23 +18,39 @@ impl TooltipComponent for Name {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Hp {
     pub max: i32,
     pub current: i32,
+    /// The part of `max` that came from buffs.
+    #[serde(default)]
+    pub bonus: AbilityModifier,
 }
 
 impl Hp {
     pub fn new(max: i32) -> Self {
-        Self { max, current: max }
+        Self {
+            max,
+            current: max,
+            bonus: AbilityModifier::default(),
+        }
     }
 
     pub fn is_dead(&self) -> bool {
         self.current <= 0
     }
 
-    pub fn max_increase(&mut self, amount: i32) {
+    pub fn max_increase(&mut self, amount: i32, scope: ModifierScope) {
         self.max += amount;
+        self.bonus.add(scope, amount);
+    }
+
+    /// Takes off any `max` bonus lasting no longer than `scope`.
+    pub fn expire_bonus(&mut self, scope: ModifierScope) {
+        self.max -= self.bonus.amount();
+        self.bonus.expire(scope);
+        self.max += self.bonus.amount();
+        self.current = self.current.min(self.max);
     }
 
     pub fn increase(&mut self, amount: i32) {
@@ -50,3 +71,8 @@ impl fmt::Display for Hp {
         write!(f, "{}/{}", self.current, self.max)
     }
 }
+
+/// Returns the value following `flag` on the command line, e.g. `--seed 42`.
+pub fn arg_value(flag: &str) -> Option<String> {
+    env::args().skip_while(|arg| arg != flag).nth(1)
+}
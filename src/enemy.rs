@@ -1,21 +1,54 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, ui::RelativeCursorPosition};
+use rand::{seq::SliceRandom, Rng as _, RngCore};
+use serde::Deserialize;
 
 use crate::{
-    assets::{GameFonts, GameSprites},
-    common::Hp,
-    items::abilities::{Ability, Damage},
-    ui::{BottomRightUI, HealthBarUI, HealthBarUIText, FONT_COLOR, FONT_SIZE},
+    assets::{GameData, GameFonts, GameSprites, RonAssetLoader},
+    battle::ActiveBattle,
+    common::{Hp, Name},
+    inventory::{spawn_loot, LootBonus},
+    items::{
+        abilities::{Ability, Damage},
+        definitions::ItemDefinitions,
+        ItemType, Rarity,
+    },
+    rng::Rng,
+    status::{StatusBarUI, StatusEffects},
+    tooltip::{TooltipComponent, TooltipSection, TooltipSectionIndex, Tooltipable},
+    ui::{BottomCenterUI, BottomRightUI, HealthBarUI, HealthBarUIText, FONT_COLOR, FONT_SIZE},
     AppState, BattleWins,
 };
 
+const ENEMY_SCROLL_UI_WIDTH: f32 = 105.;
+const ELITE_COLOR: Color = Color::GOLD;
+
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<BattleWins>()
+            .init_resource::<LootBonus>()
+            .init_asset::<EnemyDefinitions>()
+            .register_asset_loader(RonAssetLoader::<EnemyDefinitions>::new(&["enemies.ron"]))
+            .add_systems(OnEnter(AppState::InitGame), insert_enemy_definitions)
+            .add_systems(
+                OnEnter(AppState::OrganizeInventory),
+                roll_next_encounter.after(spawn_loot),
+            )
+            .add_systems(
+                Update,
+                reload_enemy_definitions
+                    .run_if(resource_exists::<GameData>)
+                    .run_if(not(in_state(AppState::LoadingAssets))),
+            )
             .add_systems(
                 OnEnter(AppState::Battling),
-                (spawn_enemy, spawn_enemy_stats_ui).chain(),
+                (spawn_enemy, spawn_enemy_stats_ui, spawn_enemy_scroll_ui).chain(),
+            )
+            .add_systems(
+                Update,
+                (update_target_ui, update_enemy_armor_ui)
+                    .run_if(resource_exists_and_changed::<ActiveBattle>),
             )
             .add_systems(OnExit(AppState::Battling), (destroy_enemy,))
             .add_systems(
@@ -31,31 +64,452 @@ impl Plugin for EnemyPlugin {
 #[derive(Component, Default, Clone, Copy)]
 pub struct Enemy;
 
+/// Position of an enemy in the current encounter, shared by its UI and
+/// sprite. Matches its index in `BattleSim::enemies`.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct EnemySlot(pub usize);
+
+/// UI that is only shown for the enemy currently being targeted.
+#[derive(Component)]
+pub struct EnemyPanelUI;
+
 #[derive(Component, Default, Clone, Copy)]
 pub struct EnemyDamageUI;
 
+#[derive(Component)]
+pub struct EnemyNameUI;
+
+/// Armor the enemy has in the battle, including elite affixes and armor
+/// gained from boss mechanics. Hidden while it has none.
+#[derive(Component)]
+pub struct EnemyArmorUI;
+
+#[derive(Component)]
+pub struct EnemyScrollUI;
+
+/// A stat that grows with the number of battles won:
+/// `base + per_win * wins ^ exponent`.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct Curve {
+    pub base: f32,
+    pub per_win: f32,
+    pub exponent: f32,
+}
+
+impl Curve {
+    pub fn at(&self, battle_wins: &BattleWins) -> i32 {
+        (self.base + self.per_win * (battle_wins.0 as f32).powf(self.exponent)) as i32
+    }
+}
+
+/// What an enemy does on its turn besides hitting the player.
+#[derive(Component, Deserialize, Default, Clone, Copy, Debug)]
+pub enum EnemyBehaviour {
+    #[default]
+    Strike,
+    /// Hits harder by this much after every attack.
+    Berserk(i32),
+    /// Heals by the damage it deals.
+    Leech,
+}
+
+/// When an enemy's patience runs out in a long battle.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnrageRule {
+    /// Every turn once this many turns have gone by.
+    AfterTurns(usize),
+    /// Every time the player's scroll marker wraps back to the start.
+    PerPass,
+}
+
+/// Makes an enemy hit harder by `damage` each time its `rule` triggers.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Enrage {
+    pub rule: EnrageRule,
+    pub damage: i32,
+}
+
+impl Enrage {
+    /// How many times the rule has triggered after `turns` turns and
+    /// `passes` full passes of the player's scroll.
+    pub fn stacks(&self, turns: usize, passes: usize) -> usize {
+        match self.rule {
+            EnrageRule::AfterTurns(after) => turns.saturating_sub(after),
+            EnrageRule::PerPass => passes,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self.rule {
+            EnrageRule::AfterTurns(after) => format!("Enrages after {after} turns"),
+            EnrageRule::PerPass => "Enrages every pass".to_string(),
+        }
+    }
+}
+
+/// A randomly rolled modifier that makes an enemy elite.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnemyAffix {
+    /// Takes this much off every attack against the enemy.
+    Armored(i32),
+    /// Heals by the damage its attacks deal.
+    Vampiric,
+    /// The player's `Cursed` items hurt twice as much.
+    CursedAura,
+    /// Attacks twice each turn.
+    Swift,
+}
+
+impl EnemyAffix {
+    /// Whether both are the same affix, whatever their amounts.
+    fn same_kind(&self, other: &EnemyAffix) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            EnemyAffix::Armored(armor) => format!("Armored {armor}"),
+            EnemyAffix::Vampiric => "Vampiric".to_string(),
+            EnemyAffix::CursedAura => "Cursed Aura".to_string(),
+            EnemyAffix::Swift => "Swift".to_string(),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            EnemyAffix::Armored(armor) => format!("Takes {armor} off every hit"),
+            EnemyAffix::Vampiric => "Heals by the damage it deals".to_string(),
+            EnemyAffix::CursedAura => "Your Cursed items hurt twice".to_string(),
+            EnemyAffix::Swift => "Attacks twice".to_string(),
+        }
+    }
+}
+
+/// The affixes rolled on an enemy, shown in the tooltip on its name.
+#[derive(Component, Clone, Default, Debug)]
+pub struct EnemyAffixes(pub Vec<EnemyAffix>);
+
+impl TooltipComponent for EnemyAffixes {
+    fn get_tooltip_section(&self) -> TooltipSection {
+        let lines: Vec<String> = self
+            .0
+            .iter()
+            .map(|affix| format!("{}: {}", affix.name(), affix.description()))
+            .collect();
+        TooltipSection {
+            text: lines.join("\n"),
+            index: TooltipSectionIndex::Body,
+            color: ELITE_COLOR,
+        }
+    }
+}
+
+/// A scripted move a boss makes at the start of each of its turns.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BossMechanic {
+    /// Curses a random item on the player's scroll by this much for the rest
+    /// of the battle.
+    CurseItem(i32),
+    /// Gains `amount` armor every `every` turns.
+    GainArmor { every: usize, amount: i32 },
+}
+
+impl BossMechanic {
+    pub fn description(&self) -> String {
+        match self {
+            BossMechanic::CurseItem(amount) => format!("Curses an item by {amount} each turn"),
+            BossMechanic::GainArmor { every, amount } => {
+                format!("Gains {amount} armor every {every} turns")
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EnemySprite {
+    pub path: String,
+    pub tile_size: (f32, f32),
+    pub columns: usize,
+    pub idle_frames: usize,
+    #[serde(default)]
+    pub attack_frame: Option<usize>,
+    #[serde(default)]
+    pub flip_x: bool,
+    #[serde(default = "default_sprite_scale")]
+    pub scale: f32,
+}
+
+fn default_sprite_scale() -> f32 {
+    1.
+}
+
+#[derive(Component, Deserialize, Clone, Debug)]
+pub struct EnemyArchetype {
+    pub id: String,
+    pub name: String,
+    pub sprite: EnemySprite,
+    pub hp: Curve,
+    pub damage: Curve,
+    #[serde(default)]
+    pub behaviour: EnemyBehaviour,
+    /// Taken off every attack against the enemy.
+    #[serde(default)]
+    pub armor: i32,
+    #[serde(default)]
+    pub enrage: Option<Enrage>,
+    #[serde(default)]
+    pub mechanics: Vec<BossMechanic>,
+    /// A rarity of item guaranteed to drop once the enemy is beaten.
+    #[serde(default)]
+    pub loot: Option<Rarity>,
+    /// Usually rolled from `EnemyDefinitions::elites` rather than set here.
+    #[serde(default)]
+    pub affixes: Vec<EnemyAffix>,
+    /// Items the enemy uses in order, one per turn.
+    #[serde(default)]
+    pub items: Vec<ItemType>,
+}
+
+/// Archetypes that can be fought once the player has won `from_wins` battles,
+/// in groups of between `group_size.0` and `group_size.1` enemies.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Encounter {
+    pub from_wins: usize,
+    pub archetypes: Vec<String>,
+    #[serde(default = "single_enemy")]
+    pub group_size: (usize, usize),
+}
+
+fn single_enemy() -> (usize, usize) {
+    (1, 1)
+}
+
+/// Boss fights that replace every `every`th battle, taking turns in order.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Bosses {
+    pub every: usize,
+    pub archetypes: Vec<String>,
+}
+
+impl Bosses {
+    /// The boss waiting in the battle after `battle_wins` wins, if any.
+    pub fn at(&self, battle_wins: &BattleWins) -> Option<&str> {
+        let battle = battle_wins.0 + 1;
+        if self.every == 0 || self.archetypes.is_empty() || !battle.is_multiple_of(self.every) {
+            return None;
+        }
+        let index = (battle / self.every - 1) % self.archetypes.len();
+        Some(&self.archetypes[index])
+    }
+}
+
+/// How often enemies roll elite affixes once the player has won `from_wins`
+/// battles. Elites get between one and `max_affixes` of `affixes`.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Elites {
+    pub from_wins: usize,
+    /// Percent chance for each enemy to be elite.
+    pub chance: u32,
+    pub max_affixes: usize,
+    pub affixes: Vec<EnemyAffix>,
+}
+
+impl Elites {
+    fn roll(
+        &self,
+        archetype: &mut EnemyArchetype,
+        battle_wins: &BattleWins,
+        rng: &mut impl RngCore,
+    ) {
+        if battle_wins.0 < self.from_wins || rng.gen_range(0..100) >= self.chance {
+            return;
+        }
+        // An archetype never gets a second affix of a kind it already has.
+        let available: Vec<EnemyAffix> = self
+            .affixes
+            .iter()
+            .filter(|affix| !archetype.affixes.iter().any(|has| has.same_kind(affix)))
+            .copied()
+            .collect();
+        if available.is_empty() {
+            return;
+        }
+        let count = rng.gen_range(1..=self.max_affixes.max(1));
+        archetype
+            .affixes
+            .extend(available.choose_multiple(rng, count).copied());
+    }
+}
+
+/// Every enemy in the game, loaded from `assets/data/*.enemies.ron`.
+#[derive(Asset, Resource, TypePath, Deserialize, Clone, Debug)]
+pub struct EnemyDefinitions {
+    pub archetypes: Vec<EnemyArchetype>,
+    pub encounters: Vec<Encounter>,
+    #[serde(default)]
+    pub bosses: Option<Bosses>,
+    #[serde(default)]
+    pub elites: Option<Elites>,
+}
+
+impl EnemyDefinitions {
+    pub fn get(&self, id: &str) -> Option<&EnemyArchetype> {
+        self.archetypes.iter().find(|archetype| archetype.id == id)
+    }
+
+    /// Picks the enemies for the next battle: a lone boss on milestone
+    /// battles, otherwise a group from the latest encounter the player has
+    /// reached, some of them rolled as elites.
+    pub fn encounter(
+        &self,
+        battle_wins: &BattleWins,
+        rng: &mut impl RngCore,
+    ) -> Vec<EnemyArchetype> {
+        let boss = self
+            .bosses
+            .as_ref()
+            .and_then(|bosses| bosses.at(battle_wins));
+        if let Some(boss) = boss.and_then(|id| self.get(id)) {
+            return vec![boss.clone()];
+        }
+        let fallback = || {
+            self.archetypes
+                .first()
+                .expect("No enemy archetypes defined")
+        };
+        let Some(encounter) = self
+            .encounters
+            .iter()
+            .filter(|encounter| encounter.from_wins <= battle_wins.0)
+            .max_by_key(|encounter| encounter.from_wins)
+        else {
+            return vec![fallback().clone()];
+        };
+        let (min, max) = encounter.group_size;
+        let min = min.max(1);
+        let count = if max > min {
+            rng.gen_range(min..=max)
+        } else {
+            min
+        };
+        (0..count)
+            .map(|_| {
+                let mut archetype = encounter
+                    .archetypes
+                    .choose(rng)
+                    .and_then(|id| self.get(id))
+                    .unwrap_or_else(fallback)
+                    .clone();
+                if let Some(elites) = &self.elites {
+                    elites.roll(&mut archetype, battle_wins, rng);
+                }
+                archetype
+            })
+            .collect()
+    }
+}
+
+/// The loot earned by beating `archetypes`: their best guaranteed drop, and
+/// an upgraded item for every elite affix.
+pub fn loot_bonus(archetypes: &[EnemyArchetype]) -> LootBonus {
+    LootBonus {
+        guaranteed: archetypes
+            .iter()
+            .filter_map(|archetype| archetype.loot)
+            .max(),
+        upgrades: archetypes
+            .iter()
+            .map(|archetype| archetype.affixes.len())
+            .sum(),
+    }
+}
+
 #[derive(Bundle)]
 pub struct EnemyBundle {
     pub enemy: Enemy,
     pub hp: Hp,
     pub damage: Damage,
+    pub behaviour: EnemyBehaviour,
+    pub statuses: StatusEffects,
+    pub archetype: EnemyArchetype,
 }
 
 impl EnemyBundle {
-    fn from_battle_wins(battle_wins: &BattleWins) -> Self {
-        let hp = Hp::new(6 + 3 * (battle_wins.0 as f32).powf(1.1) as i32);
-        let damage = Damage::new((3 as f32 + battle_wins.0 as f32 * 0.1) as i32);
-
+    pub fn from_archetype(archetype: &EnemyArchetype, battle_wins: &BattleWins) -> Self {
         Self {
             enemy: Enemy,
-            hp,
-            damage,
+            hp: Hp::new(archetype.hp.at(battle_wins)),
+            damage: Damage::new(archetype.damage.at(battle_wins)),
+            behaviour: archetype.behaviour,
+            statuses: StatusEffects::default(),
+            archetype: archetype.clone(),
         }
     }
 }
 
-fn spawn_enemy(mut commands: Commands, battle_wins: Res<BattleWins>) {
-    commands.spawn(EnemyBundle::from_battle_wins(&battle_wins));
+fn insert_enemy_definitions(
+    mut commands: Commands,
+    game_data: Res<GameData>,
+    enemy_definitions: Res<Assets<EnemyDefinitions>>,
+) {
+    match enemy_definitions.get(&game_data.enemies) {
+        Some(definitions) => commands.insert_resource(definitions.clone()),
+        None => error!("Enemy definitions failed to load"),
+    }
+}
+
+fn reload_enemy_definitions(
+    mut commands: Commands,
+    mut asset_er: EventReader<AssetEvent<EnemyDefinitions>>,
+    game_data: Res<GameData>,
+    enemy_definitions: Res<Assets<EnemyDefinitions>>,
+) {
+    for event in asset_er.read() {
+        if !event.is_modified(&game_data.enemies) {
+            continue;
+        }
+        if let Some(definitions) = enemy_definitions.get(&game_data.enemies) {
+            info!("Reloaded enemy definitions");
+            commands.insert_resource(definitions.clone());
+        }
+    }
+}
+
+/// The enemies waiting at the end of the current `OrganizeInventory`, rolled
+/// as soon as the loot is so that the solver can plan against them.
+#[derive(Resource)]
+pub struct NextEncounter(pub Vec<EnemyArchetype>);
+
+pub fn roll_next_encounter(
+    mut commands: Commands,
+    mut rng: ResMut<Rng>,
+    battle_wins: Res<BattleWins>,
+    enemy_definitions: Res<EnemyDefinitions>,
+) {
+    let archetypes = enemy_definitions.encounter(&battle_wins, &mut *rng);
+    commands.insert_resource(NextEncounter(archetypes));
+}
+
+pub fn spawn_enemy(
+    mut commands: Commands,
+    mut rng: ResMut<Rng>,
+    mut bonus: ResMut<LootBonus>,
+    battle_wins: Res<BattleWins>,
+    enemy_definitions: Res<EnemyDefinitions>,
+    next_encounter: Option<Res<NextEncounter>>,
+) {
+    let archetypes = match next_encounter {
+        Some(next_encounter) => next_encounter.0.clone(),
+        None => enemy_definitions.encounter(&battle_wins, &mut *rng),
+    };
+    for (slot, archetype) in archetypes.iter().enumerate() {
+        commands.spawn((
+            EnemyBundle::from_archetype(archetype, &battle_wins),
+            EnemySlot(slot),
+        ));
+    }
+    commands.remove_resource::<NextEncounter>();
+    *bonus = loot_bonus(&archetypes);
 }
 
 fn spawn_enemy_stats_ui(
@@ -63,73 +517,464 @@ fn spawn_enemy_stats_ui(
     game_sprites: Res<GameSprites>,
     game_fonts: Res<GameFonts>,
     enemy_stats_ui_q: Query<Entity, With<BottomRightUI>>,
-    enemy_stats_q: Query<(&Hp, &Damage), With<Enemy>>,
+    enemy_stats_q: Query<(Entity, &Hp, &Damage, &EnemyArchetype, &EnemySlot), With<Enemy>>,
 ) {
-    let (hp, damage) = enemy_stats_q.single();
-    commands
-        .entity(enemy_stats_ui_q.single())
-        .with_children(|mut parent| {
-            parent.spawn((
+    for (enemy, hp, damage, archetype, &slot) in enemy_stats_q.iter() {
+        let panel = commands
+            .spawn((
                 Enemy,
-                EnemyDamageUI,
-                TextBundle {
-                    text: Text::from_sections(vec![
-                        TextSection {
-                            value: "Damage: ".to_string(),
-                            style: TextStyle {
-                                color: FONT_COLOR,
+                EnemyPanelUI,
+                slot,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::End,
+                        align_items: AlignItems::End,
+                        display: Display::None,
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                let elite = !archetype.affixes.is_empty();
+                let mut name_ui = parent.spawn((
+                    Enemy,
+                    EnemyNameUI,
+                    TextBundle::from_section(
+                        archetype.name.clone(),
+                        TextStyle {
+                            color: if elite { ELITE_COLOR } else { FONT_COLOR },
+                            font_size: FONT_SIZE,
+                            font: game_fonts.font.clone(),
+                        },
+                    ),
+                ));
+                if elite {
+                    name_ui.insert((
+                        Name(archetype.name.clone()),
+                        EnemyAffixes(archetype.affixes.clone()),
+                        RelativeCursorPosition::default(),
+                        Tooltipable::default(),
+                    ));
+                }
+                // Enemies with a scroll show their items instead.
+                if archetype.items.is_empty() {
+                    parent.spawn((
+                        Enemy,
+                        EnemyDamageUI,
+                        slot,
+                        TextBundle {
+                            text: Text::from_sections(vec![
+                                TextSection {
+                                    value: "Damage: ".to_string(),
+                                    style: TextStyle {
+                                        color: FONT_COLOR,
+                                        font_size: FONT_SIZE,
+                                        font: game_fonts.font.clone(),
+                                    },
+                                },
+                                TextSection {
+                                    value: format!("{}", damage.amount()),
+                                    style: TextStyle {
+                                        color: FONT_COLOR,
+                                        font_size: FONT_SIZE,
+                                        font: game_fonts.font.clone(),
+                                    },
+                                },
+                            ]),
+                            ..default()
+                        },
+                    ));
+                }
+                if elite {
+                    let affixes: Vec<String> =
+                        archetype.affixes.iter().map(EnemyAffix::name).collect();
+                    parent.spawn((
+                        Enemy,
+                        TextBundle::from_section(
+                            format!("Elite: {}", affixes.join(", ")),
+                            TextStyle {
+                                color: ELITE_COLOR,
                                 font_size: FONT_SIZE,
                                 font: game_fonts.font.clone(),
                             },
+                        ),
+                    ));
+                }
+                parent.spawn((
+                    Enemy,
+                    EnemyArmorUI,
+                    slot,
+                    TextBundle {
+                        text: Text::from_sections(vec![
+                            TextSection {
+                                value: "Armor: ".to_string(),
+                                style: TextStyle {
+                                    color: FONT_COLOR,
+                                    font_size: FONT_SIZE,
+                                    font: game_fonts.font.clone(),
+                                },
+                            },
+                            TextSection {
+                                value: String::new(),
+                                style: TextStyle {
+                                    color: FONT_COLOR,
+                                    font_size: FONT_SIZE,
+                                    font: game_fonts.font.clone(),
+                                },
+                            },
+                        ]),
+                        style: Style {
+                            display: Display::None,
+                            ..default()
                         },
-                        TextSection {
-                            value: format!("{}", damage.amount()),
-                            style: TextStyle {
+                        ..default()
+                    },
+                ));
+                for mechanic in archetype.mechanics.iter() {
+                    parent.spawn((
+                        Enemy,
+                        TextBundle::from_section(
+                            mechanic.description(),
+                            TextStyle {
                                 color: FONT_COLOR,
                                 font_size: FONT_SIZE,
                                 font: game_fonts.font.clone(),
                             },
+                        ),
+                    ));
+                }
+                if let Some(enrage) = archetype.enrage {
+                    parent.spawn((
+                        Enemy,
+                        TextBundle::from_section(
+                            enrage.description(),
+                            TextStyle {
+                                color: FONT_COLOR,
+                                font_size: FONT_SIZE,
+                                font: game_fonts.font.clone(),
+                            },
+                        ),
+                    ));
+                }
+                StatusBarUI::spawn(parent, enemy, Enemy);
+                HealthBarUI::spawn(parent, &game_sprites, &game_fonts, hp, slot);
+            })
+            .id();
+        commands.entity(enemy_stats_ui_q.single()).add_child(panel);
+    }
+}
+
+pub fn spawn_enemy_scroll_ui(
+    mut commands: Commands,
+    game_sprites: Res<GameSprites>,
+    item_definitions: Res<ItemDefinitions>,
+    bottom_center_ui_q: Query<Entity, With<BottomCenterUI>>,
+    enemy_q: Query<(&EnemyArchetype, &EnemySlot), With<Enemy>>,
+) {
+    for (archetype, &slot) in enemy_q.iter() {
+        if archetype.items.is_empty() {
+            continue;
+        }
+        let enemy_scroll_ui = commands
+            .spawn((
+                Enemy,
+                EnemyScrollUI,
+                EnemyPanelUI,
+                slot,
+                ImageBundle {
+                    image: UiImage::new(game_sprites.loot_scroll.clone()),
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        column_gap: Val::Px(4.),
+                        width: Val::Px(ENEMY_SCROLL_UI_WIDTH),
+                        max_width: Val::Px(ENEMY_SCROLL_UI_WIDTH),
+                        min_width: Val::Px(ENEMY_SCROLL_UI_WIDTH),
+                        height: Val::Px(25.),
+                        padding: UiRect {
+                            left: Val::Px(2.),
+                            right: Val::Px(3.),
+                            ..default()
                         },
-                    ]),
+                        display: Display::None,
+                        ..default()
+                    },
                     ..default()
                 },
-            ));
-            HealthBarUI::spawn(&mut parent, &game_sprites, &game_fonts, &hp, Enemy);
-        });
+            ))
+            .with_children(|parent| {
+                for item_type in archetype.items.iter() {
+                    item_definitions.spawn(item_type, parent, &game_sprites);
+                }
+            })
+            .id();
+
+        commands
+            .entity(bottom_center_ui_q.single())
+            .add_child(enemy_scroll_ui);
+    }
+}
+
+fn update_target_ui(
+    mut panels_q: Query<(&mut Style, &EnemySlot), With<EnemyPanelUI>>,
+    battle: Res<ActiveBattle>,
+) {
+    for (mut style, slot) in panels_q.iter_mut() {
+        let display = if slot.0 == battle.sim.target {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+fn update_enemy_armor_ui(
+    mut armor_ui_q: Query<(&mut Text, &mut Style, &EnemySlot), With<EnemyArmorUI>>,
+    battle: Res<ActiveBattle>,
+) {
+    for (mut text, mut style, slot) in armor_ui_q.iter_mut() {
+        let armor = battle
+            .sim
+            .enemies
+            .get(slot.0)
+            .map_or(0, |enemy| enemy.stats.armor);
+        let display = if armor > 0 {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if style.display != display {
+            style.display = display;
+        }
+        let value = armor.to_string();
+        if text.sections[1].value != value {
+            text.sections[1].value = value;
+        }
+    }
 }
 
 fn update_enemy_hp_ui(
-    mut health_bar_ui: Query<&mut TextureAtlas, (With<Enemy>, With<HealthBarUI>)>,
-    mut health_bar_ui_text: Query<&mut Text, (With<Enemy>, With<HealthBarUIText>)>,
-    enemy_hp_q: Query<&Hp, With<Enemy>>,
+    mut health_bar_ui_q: Query<(&mut TextureAtlas, &EnemySlot), With<HealthBarUI>>,
+    mut health_bar_ui_text_q: Query<(&mut Text, &EnemySlot), With<HealthBarUIText>>,
+    enemy_hp_q: Query<(&Hp, &EnemySlot), With<Enemy>>,
 ) {
-    if let Ok(hp) = enemy_hp_q.get_single() {
-        health_bar_ui.single_mut().index = hp.health_bar_index();
-        health_bar_ui_text
-            .single_mut()
-            .sections
-            .get_mut(0)
-            .unwrap()
-            .value = format!("{hp}");
+    for (hp, slot) in enemy_hp_q.iter() {
+        for (mut texture_atlas, _) in health_bar_ui_q.iter_mut().filter(|(_, s)| *s == slot) {
+            texture_atlas.index = hp.health_bar_index();
+        }
+        for (mut text, _) in health_bar_ui_text_q.iter_mut().filter(|(_, s)| *s == slot) {
+            text.sections.get_mut(0).unwrap().value = format!("{hp}");
+        }
     }
 }
 
 fn update_enemy_damage_ui(
-    mut enemy_damage_ui_text: Query<&mut Text, (With<Enemy>, With<EnemyDamageUI>)>,
-    enemy_damage_q: Query<&Damage, With<Enemy>>,
+    mut enemy_damage_ui_text_q: Query<(&mut Text, &EnemySlot), With<EnemyDamageUI>>,
+    enemy_damage_q: Query<(&Damage, &EnemySlot), With<Enemy>>,
 ) {
-    if let Ok(damage) = enemy_damage_q.get_single() {
-        enemy_damage_ui_text
-            .single_mut()
-            .sections
-            .get_mut(1)
-            .unwrap()
-            .value = format!("{}", damage.amount());
+    for (damage, slot) in enemy_damage_q.iter() {
+        for (mut text, _) in enemy_damage_ui_text_q
+            .iter_mut()
+            .filter(|(_, s)| *s == slot)
+        {
+            text.sections.get_mut(1).unwrap().value = format!("{}", damage.amount());
+        }
     }
 }
 
 fn destroy_enemy(mut commands: Commands, enemies_q: Query<Entity, With<Enemy>>) {
     for entity in enemies_q.iter() {
-        commands.get_entity(entity).map(|e| e.despawn_recursive());
+        if let Some(e) = commands.get_entity(entity) {
+            e.despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enrage_stacks_follow_their_rule() {
+        let after = Enrage {
+            rule: EnrageRule::AfterTurns(3),
+            damage: 1,
+        };
+        let per_pass = Enrage {
+            rule: EnrageRule::PerPass,
+            damage: 1,
+        };
+        assert_eq!(after.stacks(2, 5), 0);
+        assert_eq!(after.stacks(5, 0), 2);
+        assert_eq!(per_pass.stacks(5, 2), 2);
+    }
+
+    #[test]
+    fn bosses_guard_every_nth_battle_in_turn() {
+        let bosses = Bosses {
+            every: 4,
+            archetypes: vec!["First".to_string(), "Second".to_string()],
+        };
+        let at = |wins| bosses.at(&BattleWins(wins));
+        assert_eq!(at(3), Some("First"));
+        assert_eq!(at(7), Some("Second"));
+        assert_eq!(at(11), Some("First"));
+        assert_eq!(at(4), None);
+        let never = Bosses {
+            every: 0,
+            archetypes: vec!["First".to_string()],
+        };
+        assert_eq!(never.at(&BattleWins(3)), None);
+    }
+
+    fn archetype(affixes: Vec<EnemyAffix>, loot: Option<Rarity>) -> EnemyArchetype {
+        let mut archetype: EnemyArchetype = ron::from_str(
+            r#"(
+                id: "Grunt",
+                name: "Grunt",
+                sprite: (path: "", tile_size: (32., 32.), columns: 1, idle_frames: 1),
+                hp: (base: 10., per_win: 0., exponent: 1.),
+                damage: (base: 1., per_win: 0., exponent: 1.),
+            )"#,
+        )
+        .unwrap();
+        archetype.affixes = affixes;
+        archetype.loot = loot;
+        archetype
+    }
+
+    #[test]
+    fn loot_bonus_takes_the_best_drop_and_an_upgrade_per_affix() {
+        let encounter = [
+            archetype(vec![EnemyAffix::Swift], Some(Rarity::Scarce)),
+            archetype(vec![EnemyAffix::Vampiric, EnemyAffix::Armored(1)], None),
+            archetype(vec![], Some(Rarity::Precious)),
+        ];
+        let bonus = loot_bonus(&encounter);
+        assert_eq!(bonus.guaranteed, Some(Rarity::Precious));
+        assert_eq!(bonus.upgrades, 3);
+        assert_eq!(loot_bonus(&[]), LootBonus::default());
+    }
+
+    #[test]
+    fn elites_only_roll_once_enough_battles_are_won() {
+        let elites = Elites {
+            from_wins: 2,
+            chance: 100,
+            max_affixes: 1,
+            affixes: vec![EnemyAffix::Swift],
+        };
+        let mut rng = Rng::from_seed(0);
+        let mut grunt = archetype(vec![], None);
+        elites.roll(&mut grunt, &BattleWins(1), &mut rng);
+        assert!(grunt.affixes.is_empty());
+        elites.roll(&mut grunt, &BattleWins(2), &mut rng);
+        assert_eq!(grunt.affixes, [EnemyAffix::Swift]);
+    }
+
+    #[test]
+    fn curves_grow_with_battle_wins() {
+        let curve = Curve {
+            base: 10.,
+            per_win: 2.,
+            exponent: 1.5,
+        };
+        assert_eq!(curve.at(&BattleWins(0)), 10);
+        assert_eq!(curve.at(&BattleWins(4)), 26);
+        let root = Curve {
+            exponent: 0.5,
+            ..curve
+        };
+        assert_eq!(root.at(&BattleWins(2)), 12);
+    }
+
+    fn definitions(encounters: Vec<Encounter>) -> EnemyDefinitions {
+        let named = |id: &str| EnemyArchetype {
+            id: id.to_string(),
+            ..archetype(vec![], None)
+        };
+        EnemyDefinitions {
+            archetypes: vec![named("Grunt"), named("Brute"), named("Captain")],
+            encounters,
+            bosses: None,
+            elites: None,
+        }
+    }
+
+    #[test]
+    fn encounters_come_from_the_latest_one_reached() {
+        let definitions = definitions(vec![
+            Encounter {
+                from_wins: 0,
+                archetypes: vec!["Grunt".to_string()],
+                group_size: (1, 1),
+            },
+            Encounter {
+                from_wins: 3,
+                archetypes: vec!["Brute".to_string(), "Captain".to_string()],
+                group_size: (1, 3),
+            },
+        ]);
+        let mut rng = Rng::from_seed(0);
+        for wins in 0..10 {
+            let (eligible, max): (&[&str], usize) = if wins < 3 {
+                (&["Grunt"], 1)
+            } else {
+                (&["Brute", "Captain"], 3)
+            };
+            for _ in 0..20 {
+                let encounter = definitions.encounter(&BattleWins(wins), &mut rng);
+                assert!((1..=max).contains(&encounter.len()));
+                for archetype in encounter {
+                    assert!(eligible.contains(&archetype.id.as_str()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn encounters_fall_back_to_the_first_archetype() {
+        let definitions = definitions(vec![Encounter {
+            from_wins: 2,
+            archetypes: vec!["Brute".to_string()],
+            group_size: (1, 1),
+        }]);
+        let encounter = definitions.encounter(&BattleWins(0), &mut Rng::from_seed(0));
+        let ids: Vec<_> = encounter.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["Grunt"]);
+    }
+
+    #[test]
+    fn elites_never_roll_an_affix_twice() {
+        let elites = Elites {
+            from_wins: 0,
+            chance: 100,
+            max_affixes: 3,
+            affixes: vec![
+                EnemyAffix::Swift,
+                EnemyAffix::Vampiric,
+                EnemyAffix::Armored(3),
+            ],
+        };
+        let mut rng = Rng::from_seed(0);
+        for _ in 0..20 {
+            let mut grunt = archetype(vec![EnemyAffix::Swift, EnemyAffix::Armored(1)], None);
+            elites.roll(&mut grunt, &BattleWins(0), &mut rng);
+            assert_eq!(
+                grunt.affixes,
+                [
+                    EnemyAffix::Swift,
+                    EnemyAffix::Armored(1),
+                    EnemyAffix::Vampiric
+                ]
+            );
+            assert_eq!(loot_bonus(&[grunt]).upgrades, 3);
+        }
     }
 }
@@ -1,11 +1,22 @@
-use bevy::{prelude::*, ui::RelativeCursorPosition, window::PrimaryWindow};
+use bevy::{
+    prelude::*,
+    tasks::{block_on, poll_once, AsyncComputeTaskPool, Task},
+    ui::RelativeCursorPosition,
+    window::PrimaryWindow,
+};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     assets::{GameFonts, GameSprites},
-    items::{Item, ItemType, Rarity},
+    battle::sim::{SimEnemy, SimPlayer},
+    common::Hp,
+    enemy::NextEncounter,
+    items::{definitions::ItemDefinitions, Item, ItemComponentsQueryReadOnly, ItemType, Rarity},
+    player::Player,
+    solver::{solve_arrangement, Solution, SolverGoal},
     tooltip::Tooltipable,
-    ui::{BottomCenterUI, BottomRightUI, TopInventoryUI, FONT_COLOR},
+    ui::{BottomCenterUI, BottomRightUI, RootUINode, TopInventoryUI, FONT_COLOR},
     AppState, BattleWins,
 };
 
@@ -20,7 +31,13 @@ impl Plugin for InventoryPlugin {
             .add_systems(OnExit(AppState::GameOver), cleanup_inventory_scroll)
             .add_systems(
                 OnEnter(AppState::OrganizeInventory),
-                (spawn_loot_scroll_ui, spawn_loot, spawn_start_battle_button).chain(),
+                (
+                    spawn_loot_scroll_ui,
+                    spawn_loot,
+                    spawn_start_battle_button,
+                    spawn_suggest_button,
+                )
+                    .chain(),
             )
             .add_systems(
                 OnExit(AppState::OrganizeInventory),
@@ -35,6 +52,14 @@ impl Plugin for InventoryPlugin {
             .add_systems(
                 Update,
                 button_system.run_if(any_with_component::<StartBattleButton>),
+            )
+            .add_systems(
+                Update,
+                suggest_button_system.run_if(any_with_component::<SuggestButton>),
+            )
+            .add_systems(
+                Update,
+                apply_suggestion.run_if(any_with_component::<Suggestion>),
             );
     }
 }
@@ -57,8 +82,16 @@ pub struct LootScrollUI;
 #[derive(Component)]
 struct StartBattleButton;
 
-#[derive(Component, Clone, Copy)]
-struct ItemUI(Entity);
+#[derive(Component)]
+struct SuggestButton(SolverGoal);
+
+/// A search for a better order of the inventory scroll, running off the main
+/// thread. `items` is the scroll as it was when the search started.
+#[derive(Component)]
+struct Suggestion {
+    items: Vec<Entity>,
+    task: Task<Solution>,
+}
 
 #[derive(Component)]
 struct DragContainer;
@@ -72,36 +105,71 @@ struct Dragging {
     last_index: usize,
 }
 
-fn spawn_loot(
+pub fn spawn_loot(
     mut commands: Commands,
-    mut rng: NonSendMut<crate::rng::Rng>,
+    mut rng: ResMut<crate::rng::Rng>,
     battle_wins: Res<BattleWins>,
     game_sprites: Res<GameSprites>,
+    item_definitions: Res<ItemDefinitions>,
+    loot_bonus: Res<LootBonus>,
     loot_scroll_q: Query<Entity, With<LootScrollUI>>,
 ) {
+    let loot = roll_loot(&item_definitions, &battle_wins, *loot_bonus, &mut *rng);
     commands
         .entity(loot_scroll_q.single())
         .with_children(|parent| {
-            if battle_wins.0 == 0 {
-                ItemType::WoodenSword.spawn(parent, &game_sprites);
-                ItemType::Grog.spawn(parent, &game_sprites);
-            } else {
-                for _ in 0..rng.0.gen_range(3..=4) {
-                    let item_type = if rng.0.gen_ratio(2, 5) {
-                        Rarity::Mundane.rand_item(&mut rng)
-                    } else if rng.0.gen_ratio(3, 5) {
-                        Rarity::Scarce.rand_item(&mut rng)
-                    } else if rng.0.gen_ratio(4, 5) {
-                        Rarity::Precious.rand_item(&mut rng)
-                    } else {
-                        Rarity::Mythic.rand_item(&mut rng)
-                    };
-                    item_type.spawn(parent, &game_sprites);
-                }
+            for item_type in loot.iter() {
+                item_definitions.spawn(item_type, parent, &game_sprites);
             }
         });
 }
 
+/// Extra loot earned by beating bosses and elites, set for the battle being
+/// fought or the last one once it is over.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct LootBonus {
+    /// A rarity of item added on top of the usual rolls.
+    pub guaranteed: Option<Rarity>,
+    /// How many of the rolled items move up a rarity.
+    pub upgrades: usize,
+}
+
+pub fn roll_loot(
+    item_definitions: &ItemDefinitions,
+    battle_wins: &BattleWins,
+    bonus: LootBonus,
+    rng: &mut impl Rng,
+) -> Vec<ItemType> {
+    if battle_wins.0 == 0 {
+        return item_definitions.starting_loot.clone();
+    }
+    let mut loot: Vec<ItemType> = (0..rng.gen_range(3..=4))
+        .filter_map(|i| {
+            let rarity = if rng.gen_ratio(2, 5) {
+                Rarity::Mundane
+            } else if rng.gen_ratio(3, 5) {
+                Rarity::Scarce
+            } else if rng.gen_ratio(4, 5) {
+                Rarity::Precious
+            } else {
+                Rarity::Mythic
+            };
+            let rarity = if i < bonus.upgrades {
+                rarity.upgrade()
+            } else {
+                rarity
+            };
+            item_definitions.rand_item(rarity, rng)
+        })
+        .collect();
+    loot.extend(
+        bonus
+            .guaranteed
+            .and_then(|rarity| item_definitions.rand_item(rarity, rng)),
+    );
+    loot
+}
+
 fn spawn_inventory_scroll(
     mut commands: Commands,
     top_inventory_ui_q: Query<Entity, With<TopInventoryUI>>,
@@ -260,7 +328,65 @@ fn destroy_loot_scroll_ui(
     }
 }
 
-fn destroy_buttons(mut commands: Commands, buttons_q: Query<Entity, With<StartBattleButton>>) {
+fn spawn_suggest_button(
+    mut commands: Commands,
+    game_sprites: Res<GameSprites>,
+    game_fonts: Res<GameFonts>,
+    root_ui_q: Query<Entity, With<RootUINode>>,
+) {
+    for (goal, label) in [
+        (SolverGoal::FastestKill, "Fastest Kill"),
+        (SolverGoal::LeastDamage, "Least Damage"),
+    ] {
+        let suggest_button = commands
+            .spawn((
+                SuggestButton(goal),
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(65.),
+                        height: Val::Px(16.),
+                        margin: UiRect {
+                            top: Val::Px(4.),
+                            ..default()
+                        },
+                        padding: UiRect {
+                            left: Val::Px(4.),
+                            top: Val::Px(6.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    image: game_sprites.suggest_button.clone().into(),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let button_text = commands
+            .spawn(TextBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        color: FONT_COLOR,
+                        font_size: 7.,
+                        font: game_fonts.font.clone(),
+                    },
+                ),
+                ..default()
+            })
+            .id();
+
+        commands.entity(suggest_button).add_child(button_text);
+        commands
+            .entity(root_ui_q.single())
+            .add_child(suggest_button);
+    }
+}
+
+fn destroy_buttons(
+    mut commands: Commands,
+    buttons_q: Query<Entity, Or<(With<StartBattleButton>, With<SuggestButton>)>>,
+) {
     for button in buttons_q.iter() {
         commands.entity(button).despawn_recursive();
     }
@@ -274,11 +400,109 @@ fn button_system(
     let (interaction, mut image) = interaction_q.single_mut();
     match *interaction {
         Interaction::Pressed => app_state.set(AppState::Battling),
-        Interaction::Hovered => image.texture = game_sprites.start_battle_button_hover.clone(),
-        Interaction::None => image.texture = game_sprites.start_battle_button.clone(),
+        Interaction::Hovered => image.texture = game_sprites.suggest_button_hover.clone(),
+        Interaction::None => image.texture = game_sprites.suggest_button.clone(),
     };
 }
 
+fn suggest_button_system(
+    mut commands: Commands,
+    mut interaction_q: Query<
+        (Entity, &Interaction, &SuggestButton, &mut UiImage),
+        Changed<Interaction>,
+    >,
+    suggestion_q: Query<(), With<Suggestion>>,
+    game_sprites: Res<GameSprites>,
+    battle_wins: Res<BattleWins>,
+    next_encounter: Option<Res<NextEncounter>>,
+    item_definitions: Res<ItemDefinitions>,
+    inventory_scroll_q: Query<&Children, With<InventoryScrollUI>>,
+    items_q: Query<ItemComponentsQueryReadOnly>,
+    player_hp_q: Query<&Hp, With<Player>>,
+) {
+    for (button, interaction, suggest, mut image) in interaction_q.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => (),
+            Interaction::Hovered => {
+                image.texture = game_sprites.suggest_button_hover.clone();
+                continue;
+            }
+            Interaction::None => {
+                image.texture = game_sprites.suggest_button.clone();
+                continue;
+            }
+        };
+        let (Some(next_encounter), Ok(children), Ok(hp)) = (
+            next_encounter.as_ref(),
+            inventory_scroll_q.get_single(),
+            player_hp_q.get_single(),
+        ) else {
+            continue;
+        };
+        if !suggestion_q.is_empty() {
+            continue;
+        }
+
+        let entities: Vec<Entity> = children
+            .iter()
+            .filter(|&&child| items_q.contains(child))
+            .copied()
+            .collect();
+        let items: Vec<_> = entities
+            .iter()
+            .map(|&entity| items_q.get(entity).unwrap().components())
+            .collect();
+        let enemies: Vec<_> = next_encounter
+            .0
+            .iter()
+            .map(|archetype| SimEnemy::from_archetype(archetype, &battle_wins, &item_definitions))
+            .collect();
+        let player = SimPlayer::new(hp.clone());
+        let goal = suggest.0;
+        let task = AsyncComputeTaskPool::get()
+            .spawn(async move { solve_arrangement(&items, &player, &enemies, goal) });
+        commands.entity(button).insert(Suggestion {
+            items: entities,
+            task,
+        });
+    }
+}
+
+/// Reorders the inventory scroll once a suggestion is ready, unless items
+/// were moved in the meantime.
+fn apply_suggestion(
+    mut commands: Commands,
+    mut suggestion_q: Query<(Entity, &mut Suggestion)>,
+    inventory_scroll_q: Query<(Entity, &Children), With<InventoryScrollUI>>,
+    items_q: Query<(), With<ItemType>>,
+) {
+    for (button, mut suggestion) in suggestion_q.iter_mut() {
+        let Some(solution) = block_on(poll_once(&mut suggestion.task)) else {
+            continue;
+        };
+        commands.entity(button).remove::<Suggestion>();
+        let Ok((inventory_scroll, children)) = inventory_scroll_q.get_single() else {
+            continue;
+        };
+        let current: Vec<Entity> = children
+            .iter()
+            .filter(|&&child| items_q.contains(child))
+            .copied()
+            .collect();
+        if current != suggestion.items {
+            continue;
+        }
+        let arranged: Vec<Entity> = solution
+            .order
+            .iter()
+            .map(|&i| suggestion.items[i])
+            .collect();
+        commands
+            .entity(inventory_scroll)
+            .replace_children(&arranged);
+    }
+}
+
 fn start_dragging(
     mut commands: Commands,
     mouse: Res<ButtonInput<MouseButton>>,
@@ -342,10 +566,8 @@ fn stop_dragging(
     for (drag_entity, dragging, tooltipable) in draggings_q.iter_mut() {
         let mut index = dragging.last_index;
         let mut parent = dragging.last_parent;
-        if let Some((parent_e, relative_cursor_position, scroll_ui, children)) = scroll_ui_q
-            .iter()
-            .filter(|(_, rcp, _, _)| rcp.mouse_over())
-            .next()
+        if let Some((parent_e, relative_cursor_position, scroll_ui, children)) =
+            scroll_ui_q.iter().find(|(_, rcp, _, _)| rcp.mouse_over())
         {
             if let Some(norm) = relative_cursor_position.normalized {
                 if children.map_or(0, |c| c.len()) < scroll_ui.size {
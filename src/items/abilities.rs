@@ -1,6 +1,12 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::tooltip::{TooltipComponent, TooltipSection, TooltipSectionIndex};
+use crate::{
+    status::{turns, StatusEffect, StatusKind},
+    tooltip::{TooltipComponent, TooltipSection, TooltipSectionIndex},
+};
+
+use super::attributes::AttributeQuery;
 
 pub(super) struct AbilityPlugin;
 
@@ -16,12 +22,98 @@ impl Plugin for AbilityPlugin {
         app.register_component_as::<dyn Ability, Swashbuckle>();
         app.register_component_as::<dyn Ability, Cursed>();
         app.register_component_as::<dyn Ability, Vitality>();
+        app.register_component_as::<dyn Ability, Poison>();
+        app.register_component_as::<dyn Ability, Bleed>();
+        app.register_component_as::<dyn Ability, Burn>();
+        app.register_component_as::<dyn Ability, Stun>();
+        app.register_component_as::<dyn Ability, Buff>();
+    }
+}
+
+/// How long the buffs an item hands out last.
+#[derive(
+    Component, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize,
+)]
+pub enum ModifierScope {
+    Turn,
+    #[default]
+    Battle,
+    Run,
+}
+
+impl ModifierScope {
+    pub const ALL: [ModifierScope; 3] = [
+        ModifierScope::Turn,
+        ModifierScope::Battle,
+        ModifierScope::Run,
+    ];
+
+    fn name(&self) -> String {
+        match self {
+            ModifierScope::Turn => "this turn".to_string(),
+            ModifierScope::Battle => "this battle".to_string(),
+            ModifierScope::Run => "this run".to_string(),
+        }
+    }
+}
+
+impl TooltipComponent for ModifierScope {
+    fn get_tooltip_section(&self) -> TooltipSection {
+        TooltipSection::default_color(
+            format!("Buffs last {}", self.name()),
+            TooltipSectionIndex::Footer,
+        )
     }
 }
 
-#[derive(Component, Default, Debug, Clone, Copy)]
+/// Buffs on top of an ability's base, split by how long they last.
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AbilityModifier {
-    pub amount: i32,
+    pub turn: i32,
+    pub battle: i32,
+    /// Older saves kept a single permanent `amount`.
+    #[serde(alias = "amount")]
+    pub run: i32,
+}
+
+impl AbilityModifier {
+    pub const NONE: AbilityModifier = AbilityModifier {
+        turn: 0,
+        battle: 0,
+        run: 0,
+    };
+
+    pub fn amount(&self) -> i32 {
+        self.turn + self.battle + self.run
+    }
+
+    pub fn get(&self, scope: ModifierScope) -> i32 {
+        match scope {
+            ModifierScope::Turn => self.turn,
+            ModifierScope::Battle => self.battle,
+            ModifierScope::Run => self.run,
+        }
+    }
+
+    fn get_mut(&mut self, scope: ModifierScope) -> &mut i32 {
+        match scope {
+            ModifierScope::Turn => &mut self.turn,
+            ModifierScope::Battle => &mut self.battle,
+            ModifierScope::Run => &mut self.run,
+        }
+    }
+
+    pub fn add(&mut self, scope: ModifierScope, amount: i32) {
+        *self.get_mut(scope) += amount;
+    }
+
+    /// Drops every buff that lasts no longer than `scope`.
+    pub fn expire(&mut self, scope: ModifierScope) {
+        for expired in ModifierScope::ALL.into_iter().filter(|&s| s <= scope) {
+            *self.get_mut(expired) = 0;
+        }
+    }
 }
 
 #[bevy_trait_query::queryable]
@@ -29,8 +121,9 @@ pub trait Ability {
     fn name(&self) -> String;
     fn base(&self) -> i32;
     fn modifier(&self) -> &AbilityModifier;
+    fn modifier_mut(&mut self) -> &mut AbilityModifier;
     fn amount(&self) -> i32 {
-        self.base() + self.modifier().amount
+        self.base() + self.modifier().amount()
     }
 }
 
@@ -40,32 +133,93 @@ where
 {
     fn get_tooltip_section(&self) -> TooltipSection {
         let mut text = format!("{} {}", self.name(), self.base());
-        let amount = self.modifier().amount;
-        if amount > 0 {
-            text.push_str(format!("\n\t+{}", amount).as_str());
-        } else if amount < 0 {
-            text.push_str(format!("\n\t{}", amount).as_str());
+        for scope in ModifierScope::ALL {
+            let amount = self.modifier().get(scope);
+            if amount != 0 {
+                text.push_str(format!("\n\t{:+} {}", amount, scope.name()).as_str());
+            }
         }
         TooltipSection::default_color(text, TooltipSectionIndex::Body)
     }
 }
 
-#[derive(Default, Clone, Debug)]
+/// Names an ability so effects can reach it on any item.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum AbilityKind {
+    Damage,
+    Hearties,
+    Cursed,
+    Heave,
+    SeaLegs,
+    Swashbuckle,
+    Jolly,
+    Vitality,
+    Poison,
+    Bleed,
+    Burn,
+    Stun,
+    Buff,
+}
+
+impl AbilityKind {
+    pub const ALL: [AbilityKind; 13] = [
+        AbilityKind::Damage,
+        AbilityKind::Hearties,
+        AbilityKind::Cursed,
+        AbilityKind::Heave,
+        AbilityKind::SeaLegs,
+        AbilityKind::Swashbuckle,
+        AbilityKind::Jolly,
+        AbilityKind::Vitality,
+        AbilityKind::Poison,
+        AbilityKind::Bleed,
+        AbilityKind::Burn,
+        AbilityKind::Stun,
+        AbilityKind::Buff,
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            AbilityKind::SeaLegs => "Sea legs".to_string(),
+            kind => format!("{kind:?}"),
+        }
+    }
+}
+
+/// Raises `ability` by `amount` on every item `target` picks out.
+#[derive(Clone, Debug)]
+pub struct BuffEffect {
+    /// The ability handing the buff out.
+    pub source: AbilityKind,
+    pub ability: AbilityKind,
+    pub amount: i32,
+    pub target: AbilityTarget,
+}
+
+#[derive(Default, Clone, Debug, Deserialize)]
 pub struct AbilityTarget {
     pub filter: TargetFilter,
-    pub attribute: String,
+    #[serde(default)]
+    pub attribute: AttributeQuery,
 }
 
 impl AbilityTarget {
     pub fn with_all_attributes(filter: TargetFilter) -> Self {
         Self {
             filter,
-            attribute: "".to_string(),
+            attribute: AttributeQuery::Any,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self.attribute {
+            AttributeQuery::Any => self.filter.name(),
+            _ => format!("{} {}", self.filter.name(), self.attribute),
         }
     }
 }
 
-#[derive(Default, Clone, Copy, Debug)]
+#[derive(Default, Clone, Copy, Debug, Deserialize)]
 pub enum TargetFilter {
     #[default]
     All,
@@ -74,6 +228,15 @@ pub enum TargetFilter {
     Neighbours,
     AllNext,
     AllPrev,
+    /// `n` items picked at random from those the attribute query allows.
+    Random(usize),
+    SelfOnly,
+    First,
+    Last,
+    /// Every second item counting out from this one, both ways.
+    Alternating,
+    /// Like `Next`, but carries on from the start of the scroll.
+    Wraparound(usize),
 }
 
 impl TargetFilter {
@@ -85,31 +248,38 @@ impl TargetFilter {
             TargetFilter::Neighbours => "NEIGHBOURS".to_string(),
             TargetFilter::AllNext => "ALL NEXT".to_string(),
             TargetFilter::AllPrev => "ALL PREV".to_string(),
+            TargetFilter::Random(n) => format!("{n} RANDOM"),
+            TargetFilter::SelfOnly => "SELF".to_string(),
+            TargetFilter::First => "FIRST".to_string(),
+            TargetFilter::Last => "LAST".to_string(),
+            TargetFilter::Alternating => "EVERY OTHER".to_string(),
+            TargetFilter::Wraparound(n) => format!("NEXT {n} (WRAPPING)"),
         }
     }
 
-    pub fn get_targets<'a>(
-        &self,
-        index: usize,
-        entity: Entity,
-        list: impl IntoIterator<Item = &'a Entity>,
-    ) -> Vec<Entity> {
-        let iter = list.into_iter().enumerate().filter(|(_, c)| **c != entity);
-        let targets: Vec<(usize, &Entity)> = match self {
-            TargetFilter::All => iter.collect(),
-            TargetFilter::AllNext => iter.filter(|(i, _)| *i > index).collect(),
-            TargetFilter::Next(n) => iter
-                .filter(|(i, _)| *i > index && *i <= index + n)
-                .collect(),
-            TargetFilter::AllPrev => iter.filter(|(i, _)| *i < index).collect(),
-            TargetFilter::Prev(n) => iter
-                .filter(|(i, _)| *i < index && *i + n >= index)
-                .collect(),
+    /// Candidate positions for an item at `index`. `Random` returns every
+    /// other item; the caller draws from them once attributes are checked.
+    pub fn get_targets(&self, index: usize, len: usize) -> Vec<usize> {
+        let mut iter = (0..len).filter(|i| *i != index);
+        match self {
+            TargetFilter::All | TargetFilter::Random(_) => iter.collect(),
+            TargetFilter::AllNext => iter.filter(|i| *i > index).collect(),
+            TargetFilter::Next(n) => iter.filter(|i| *i > index && *i <= index + n).collect(),
+            TargetFilter::AllPrev => iter.filter(|i| *i < index).collect(),
+            TargetFilter::Prev(n) => iter.filter(|i| *i < index && *i + n >= index).collect(),
             TargetFilter::Neighbours => iter
-                .filter(|(i, _)| *i + 1 == index || *i == index + 1)
+                .filter(|i| *i + 1 == index || *i == index + 1)
                 .collect(),
-        };
-        targets.iter().map(|(_, e)| **e).collect()
+            TargetFilter::SelfOnly => (index < len).then_some(index).into_iter().collect(),
+            TargetFilter::First => iter.next().into_iter().collect(),
+            TargetFilter::Last => iter.next_back().into_iter().collect(),
+            TargetFilter::Alternating => iter.filter(|i| i.abs_diff(index) % 2 == 0).collect(),
+            TargetFilter::Wraparound(n) => (1..=*n)
+                .take(len)
+                .map(|step| (index + step) % len)
+                .filter(|i| *i != index)
+                .collect(),
+        }
     }
 }
 
@@ -137,6 +307,10 @@ impl Ability for Damage {
     fn modifier(&self) -> &AbilityModifier {
         &self.modifier
     }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
+    }
 }
 
 #[derive(Component, Default, Debug, Clone)]
@@ -163,6 +337,10 @@ impl Ability for Hearties {
     fn modifier(&self) -> &AbilityModifier {
         &self.modifier
     }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
+    }
 }
 
 #[derive(Component, Default, Debug, Clone)]
@@ -189,6 +367,10 @@ impl Ability for Cursed {
     fn modifier(&self) -> &AbilityModifier {
         &self.modifier
     }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
+    }
 }
 
 #[derive(Component, Default, Clone, Debug)]
@@ -206,15 +388,20 @@ impl Heave {
             ..default()
         }
     }
+
+    pub fn effect(&self) -> BuffEffect {
+        BuffEffect {
+            source: AbilityKind::Heave,
+            ability: AbilityKind::Damage,
+            amount: self.amount(),
+            target: self.target.clone(),
+        }
+    }
 }
 
 impl Ability for Heave {
     fn name(&self) -> String {
-        format!(
-            "Heave ({} {})",
-            self.target.filter.name(),
-            self.target.attribute
-        )
+        format!("Heave ({})", self.target.name())
     }
 
     fn base(&self) -> i32 {
@@ -224,27 +411,41 @@ impl Ability for Heave {
     fn modifier(&self) -> &AbilityModifier {
         &self.modifier
     }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
+    }
 }
 
 #[derive(Component, Default, Clone, Debug)]
 pub struct Swashbuckle {
     pub base: i32,
+    pub modifier: AbilityModifier,
     pub target: AbilityTarget,
 }
 
 impl Swashbuckle {
     pub fn new(base: i32, target: AbilityTarget) -> Self {
-        Self { base, target }
+        Self {
+            base,
+            target,
+            ..default()
+        }
+    }
+
+    pub fn effect(&self) -> BuffEffect {
+        BuffEffect {
+            source: AbilityKind::Swashbuckle,
+            ability: AbilityKind::SeaLegs,
+            amount: self.amount(),
+            target: self.target.clone(),
+        }
     }
 }
 
 impl Ability for Swashbuckle {
     fn name(&self) -> String {
-        format!(
-            "Swashbuckle ({} {})",
-            self.target.filter.name(),
-            self.target.attribute
-        )
+        format!("Swashbuckle ({})", self.target.name())
     }
 
     fn base(&self) -> i32 {
@@ -252,7 +453,11 @@ impl Ability for Swashbuckle {
     }
 
     fn modifier(&self) -> &AbilityModifier {
-        &AbilityModifier { amount: 0 }
+        &self.modifier
+    }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
     }
 }
 
@@ -280,27 +485,41 @@ impl Ability for SeaLegs {
     fn modifier(&self) -> &AbilityModifier {
         &self.modifier
     }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
+    }
 }
 
 #[derive(Component, Default, Clone, Debug)]
 pub struct Jolly {
     pub base: i32,
+    pub modifier: AbilityModifier,
     pub target: AbilityTarget,
 }
 
 impl Jolly {
     pub fn new(base: i32, target: AbilityTarget) -> Self {
-        Self { base, target }
+        Self {
+            base,
+            target,
+            ..default()
+        }
+    }
+
+    pub fn effect(&self) -> BuffEffect {
+        BuffEffect {
+            source: AbilityKind::Jolly,
+            ability: AbilityKind::Hearties,
+            amount: self.amount(),
+            target: self.target.clone(),
+        }
     }
 }
 
 impl Ability for Jolly {
     fn name(&self) -> String {
-        format!(
-            "Jolly ({} {})",
-            self.target.filter.name(),
-            self.target.attribute
-        )
+        format!("Jolly ({})", self.target.name())
     }
 
     fn base(&self) -> i32 {
@@ -308,18 +527,23 @@ impl Ability for Jolly {
     }
 
     fn modifier(&self) -> &AbilityModifier {
-        &AbilityModifier { amount: 0 }
+        &self.modifier
+    }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
     }
 }
 
 #[derive(Component, Default, Clone, Debug)]
 pub struct Vitality {
     pub base: i32,
+    pub modifier: AbilityModifier,
 }
 
 impl Vitality {
     pub fn new(base: i32) -> Self {
-        Self { base }
+        Self { base, ..default() }
     }
 }
 
@@ -333,6 +557,318 @@ impl Ability for Vitality {
     }
 
     fn modifier(&self) -> &AbilityModifier {
-        &AbilityModifier { amount: 0 }
+        &self.modifier
+    }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
+    }
+}
+
+#[derive(Component, Default, Clone, Debug)]
+pub struct Poison {
+    pub base: i32,
+    pub modifier: AbilityModifier,
+    pub duration: i32,
+}
+
+impl Poison {
+    pub fn new(base: i32, duration: i32) -> Self {
+        Self {
+            base,
+            duration,
+            ..default()
+        }
+    }
+
+    pub fn effect(&self) -> StatusEffect {
+        StatusEffect {
+            kind: StatusKind::Poison,
+            stacks: self.amount(),
+            duration: self.duration,
+        }
+    }
+}
+
+impl Ability for Poison {
+    fn name(&self) -> String {
+        format!("Poison ({})", turns(self.duration))
+    }
+
+    fn base(&self) -> i32 {
+        self.base
+    }
+
+    fn modifier(&self) -> &AbilityModifier {
+        &self.modifier
+    }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
+    }
+}
+
+#[derive(Component, Default, Clone, Debug)]
+pub struct Bleed {
+    pub base: i32,
+    pub modifier: AbilityModifier,
+    pub duration: i32,
+}
+
+impl Bleed {
+    pub fn new(base: i32, duration: i32) -> Self {
+        Self {
+            base,
+            duration,
+            ..default()
+        }
+    }
+
+    pub fn effect(&self) -> StatusEffect {
+        StatusEffect {
+            kind: StatusKind::Bleed,
+            stacks: self.amount(),
+            duration: self.duration,
+        }
+    }
+}
+
+impl Ability for Bleed {
+    fn name(&self) -> String {
+        format!("Bleed ({})", turns(self.duration))
+    }
+
+    fn base(&self) -> i32 {
+        self.base
+    }
+
+    fn modifier(&self) -> &AbilityModifier {
+        &self.modifier
+    }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
+    }
+}
+
+/// Burns for `amount` damage, one less each turn until it goes out.
+#[derive(Component, Default, Clone, Debug)]
+pub struct Burn {
+    pub base: i32,
+    pub modifier: AbilityModifier,
+}
+
+impl Burn {
+    pub fn new(base: i32) -> Self {
+        Self { base, ..default() }
+    }
+
+    pub fn effect(&self) -> StatusEffect {
+        StatusEffect {
+            kind: StatusKind::Burn,
+            stacks: self.amount(),
+            duration: self.amount(),
+        }
+    }
+}
+
+impl Ability for Burn {
+    fn name(&self) -> String {
+        "Burn".to_string()
+    }
+
+    fn base(&self) -> i32 {
+        self.base
+    }
+
+    fn modifier(&self) -> &AbilityModifier {
+        &self.modifier
+    }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
+    }
+}
+
+/// Makes the target skip its next `amount` turns.
+#[derive(Component, Default, Clone, Debug)]
+pub struct Stun {
+    pub base: i32,
+    pub modifier: AbilityModifier,
+}
+
+impl Stun {
+    pub fn new(base: i32) -> Self {
+        Self { base, ..default() }
+    }
+
+    pub fn effect(&self) -> StatusEffect {
+        StatusEffect {
+            kind: StatusKind::Stun,
+            stacks: 1,
+            duration: self.amount(),
+        }
+    }
+}
+
+impl Ability for Stun {
+    fn name(&self) -> String {
+        "Stun".to_string()
+    }
+
+    fn base(&self) -> i32 {
+        self.base
+    }
+
+    fn modifier(&self) -> &AbilityModifier {
+        &self.modifier
+    }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
+    }
+}
+
+/// Raises any other ability on the items it targets.
+#[derive(Component, Clone, Debug)]
+pub struct Buff {
+    pub base: i32,
+    pub modifier: AbilityModifier,
+    pub ability: AbilityKind,
+    pub target: AbilityTarget,
+}
+
+impl Buff {
+    pub fn new(base: i32, ability: AbilityKind, target: AbilityTarget) -> Self {
+        Self {
+            base,
+            modifier: AbilityModifier::default(),
+            ability,
+            target,
+        }
+    }
+
+    pub fn effect(&self) -> BuffEffect {
+        BuffEffect {
+            source: AbilityKind::Buff,
+            ability: self.ability,
+            amount: self.amount(),
+            target: self.target.clone(),
+        }
+    }
+}
+
+impl Ability for Buff {
+    fn name(&self) -> String {
+        format!("Buff {} ({})", self.ability.name(), self.target.name())
+    }
+
+    fn base(&self) -> i32 {
+        self.base
+    }
+
+    fn modifier(&self) -> &AbilityModifier {
+        &self.modifier
+    }
+
+    fn modifier_mut(&mut self) -> &mut AbilityModifier {
+        &mut self.modifier
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        battle::sim::{BattleSim, SimEnemy, SimPlayer},
+        common::Hp,
+        enemy::EnemyBehaviour,
+        items::{attributes::Pointy, ItemComponents, ItemType},
+    };
+
+    use super::*;
+
+    fn buffed() -> AbilityModifier {
+        let mut modifier = AbilityModifier::default();
+        for (scope, amount) in ModifierScope::ALL.into_iter().zip([1, 2, 4]) {
+            modifier.add(scope, amount);
+        }
+        modifier
+    }
+
+    #[test]
+    fn expiring_a_scope_drops_every_shorter_scope_too() {
+        let expired: Vec<i32> = ModifierScope::ALL
+            .into_iter()
+            .map(|scope| {
+                let mut modifier = buffed();
+                modifier.expire(scope);
+                modifier.amount()
+            })
+            .collect();
+        assert_eq!(expired, [6, 4, 0]);
+    }
+
+    #[test]
+    fn filters_pick_positions_around_the_item() {
+        let targets = |filter: TargetFilter, index| filter.get_targets(index, 6);
+        assert_eq!(targets(TargetFilter::Next(2), 1), [2, 3]);
+        assert_eq!(targets(TargetFilter::Prev(2), 1), [0]);
+        assert_eq!(targets(TargetFilter::Neighbours, 0), [1]);
+        assert_eq!(targets(TargetFilter::First, 0), [1]);
+        assert_eq!(targets(TargetFilter::Last, 5), [4]);
+        assert_eq!(targets(TargetFilter::SelfOnly, 3), [3]);
+        assert_eq!(targets(TargetFilter::Random(1), 2), [0, 1, 3, 4, 5]);
+    }
+
+    #[test]
+    fn alternating_counts_out_both_ways() {
+        assert_eq!(TargetFilter::Alternating.get_targets(2, 6), [0, 4]);
+        assert_eq!(TargetFilter::Alternating.get_targets(3, 6), [1, 5]);
+    }
+
+    #[test]
+    fn wraparound_carries_on_from_the_start() {
+        assert_eq!(TargetFilter::Wraparound(2).get_targets(4, 5), [0, 1]);
+        assert_eq!(TargetFilter::Next(2).get_targets(4, 5), Vec::<usize>::new());
+        assert_eq!(TargetFilter::Wraparound(5).get_targets(1, 3), [2, 0]);
+    }
+
+    #[test]
+    fn generic_buffs_raise_the_ability_on_filtered_items() {
+        let sword = |pointy: bool| {
+            let mut item = ItemComponents::new(ItemType::new("Sword"));
+            item.damage = Some(Damage::new(1));
+            item.pointy = pointy.then_some(Pointy);
+            item
+        };
+        let mut buffer = ItemComponents::new(ItemType::new("Whetstone"));
+        buffer.buff = Some(Buff::new(
+            2,
+            AbilityKind::Damage,
+            AbilityTarget {
+                filter: TargetFilter::Next(3),
+                attribute: AttributeQuery::try_from("Pointy".to_string()).unwrap(),
+            },
+        ));
+        let mut charm = ItemComponents::new(ItemType::new("Charm"));
+        charm.pointy = Some(Pointy);
+        let items = vec![buffer, sword(true), sword(false), charm, sword(true)];
+        let enemy = SimEnemy::new(Hp::new(20), Damage::new(0), EnemyBehaviour::Strike, vec![]);
+        let mut sim = BattleSim::new(items, SimPlayer::new(Hp::new(20)), vec![enemy]);
+
+        sim.player_turn();
+        let damage: Vec<_> = (sim.items.iter())
+            .map(|item| item.damage.as_ref().map(|damage| damage.amount()))
+            .collect();
+        assert_eq!(damage, [None, Some(3), Some(1), None, Some(1)]);
+    }
+
+    #[test]
+    fn buffs_add_to_the_base_amount() {
+        let mut damage = Damage::new(3);
+        *damage.modifier_mut() = buffed();
+        assert_eq!(damage.amount(), 10);
+        damage.modifier_mut().expire(ModifierScope::Battle);
+        assert_eq!(damage.amount(), 7);
     }
 }
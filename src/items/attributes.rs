@@ -1,15 +1,16 @@
+use std::fmt;
+
 use bevy::prelude::*;
+use serde::Deserialize;
 
 use crate::tooltip::{TooltipComponent, TooltipSection, TooltipSectionIndex};
 
-use super::abilities::AbilityTarget;
+use super::abilities::{AbilityKind, AbilityTarget};
 
 pub(super) struct AttributePlugin;
 
-pub const POINTY: &str = "Pointy";
-pub const FLINTLOCK: &str = "Flintlock";
-pub const PELLETS: &str = "Pellets";
-pub const CANNONBALL: &str = "Cannonball";
+const AMMO_PIP_COLOR: Color = Color::GOLD;
+const AMMO_PIP_EMPTY_COLOR: Color = Color::rgba(0., 0., 0., 0.5);
 
 impl Plugin for AttributePlugin {
     fn build(&self, app: &mut App) {
@@ -19,15 +20,197 @@ impl Plugin for AttributePlugin {
         app.register_component_as::<dyn Attribute, Flintlock>();
         app.register_component_as::<dyn Attribute, Pellets>();
         app.register_component_as::<dyn Attribute, Cannonball>();
+        app.register_component_as::<dyn Attribute, Spread>();
+
+        app.add_systems(PostUpdate, update_ammo_pips);
+    }
+}
+
+/// Every attribute an item can carry, one per `Attribute` component.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum AttributeTag {
+    Pointy,
+    Flintlock,
+    Pellets,
+    Cannonball,
+    Spread,
+}
+
+impl AttributeTag {
+    pub const ALL: [AttributeTag; 5] = [
+        AttributeTag::Pointy,
+        AttributeTag::Flintlock,
+        AttributeTag::Pellets,
+        AttributeTag::Cannonball,
+        AttributeTag::Spread,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttributeTag::Pointy => "Pointy",
+            AttributeTag::Flintlock => "Flintlock",
+            AttributeTag::Pellets => "Pellets",
+            AttributeTag::Cannonball => "Cannonball",
+            AttributeTag::Spread => "Spread",
+        }
+    }
+}
+
+/// What a flintlock is bored for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum AmmoKind {
+    Shot,
+    Ball,
+}
+
+/// The ammo attributes each kind of flintlock can be loaded with.
+const AMMO_COMPATIBILITY: [(AmmoKind, &[AttributeTag]); 2] = [
+    (AmmoKind::Shot, &[AttributeTag::Pellets]),
+    (AmmoKind::Ball, &[AttributeTag::Cannonball]),
+];
+
+impl AmmoKind {
+    pub fn accepts(&self) -> &'static [AttributeTag] {
+        AMMO_COMPATIBILITY
+            .iter()
+            .find(|(kind, _)| kind == self)
+            .map(|(_, ammo)| *ammo)
+            .unwrap_or_default()
+    }
+}
+
+/// A single thing an `AttributeQuery` can ask an item for: one of its
+/// attributes, or one of its abilities.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QueryTag {
+    Attribute(AttributeTag),
+    Ability(AbilityKind),
+}
+
+impl QueryTag {
+    fn parse(word: &str) -> Result<Self, String> {
+        let attribute = AttributeTag::ALL
+            .into_iter()
+            .find(|tag| tag.name() == word)
+            .map(QueryTag::Attribute);
+        let ability = AbilityKind::ALL
+            .into_iter()
+            .find(|kind| format!("{kind:?}") == word)
+            .map(QueryTag::Ability);
+        attribute
+            .or(ability)
+            .ok_or_else(|| format!("unknown attribute or ability `{word}`"))
+    }
+}
+
+impl fmt::Display for QueryTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryTag::Attribute(tag) => write!(f, "{}", tag.name()),
+            QueryTag::Ability(kind) => write!(f, "{kind:?}"),
+        }
+    }
+}
+
+/// Which items an effect may land on, written in item data as e.g.
+/// `"Pointy and not Cursed"` or `"Pellets or Cannonball"`. `and` binds
+/// tighter than `or`; an empty query matches every item.
+#[derive(Default, Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum AttributeQuery {
+    #[default]
+    Any,
+    Has(QueryTag),
+    Not(Box<AttributeQuery>),
+    And(Vec<AttributeQuery>),
+    Or(Vec<AttributeQuery>),
+}
+
+impl AttributeQuery {
+    /// `has` says whether the item carries a single tag.
+    pub fn matches(&self, has: &dyn Fn(QueryTag) -> bool) -> bool {
+        match self {
+            AttributeQuery::Any => true,
+            AttributeQuery::Has(tag) => has(*tag),
+            AttributeQuery::Not(query) => !query.matches(has),
+            AttributeQuery::And(queries) => queries.iter().all(|q| q.matches(has)),
+            AttributeQuery::Or(queries) => queries.iter().any(|q| q.matches(has)),
+        }
+    }
+
+    fn parse_or(words: &[&str]) -> Result<Self, String> {
+        let terms = words
+            .split(|&word| word == "or")
+            .map(Self::parse_and)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::collapse(terms, AttributeQuery::Or))
+    }
+
+    fn parse_and(words: &[&str]) -> Result<Self, String> {
+        let terms = words
+            .split(|&word| word == "and")
+            .map(Self::parse_not)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::collapse(terms, AttributeQuery::And))
+    }
+
+    fn parse_not(words: &[&str]) -> Result<Self, String> {
+        match words {
+            ["not", rest @ ..] => Ok(AttributeQuery::Not(Box::new(Self::parse_not(rest)?))),
+            [word] => Ok(AttributeQuery::Has(QueryTag::parse(word)?)),
+            [] => Err("expected an attribute name".to_string()),
+            _ => Err(format!("expected `and` or `or` in \"{}\"", words.join(" "))),
+        }
+    }
+
+    fn collapse(mut terms: Vec<Self>, join: fn(Vec<Self>) -> Self) -> Self {
+        if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            join(terms)
+        }
+    }
+}
+
+impl TryFrom<String> for AttributeQuery {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if words.is_empty() {
+            return Ok(AttributeQuery::Any);
+        }
+        Self::parse_or(&words)
+    }
+}
+
+impl fmt::Display for AttributeQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |queries: &[AttributeQuery], sep: &str| {
+            queries
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+        match self {
+            AttributeQuery::Any => Ok(()),
+            AttributeQuery::Has(tag) => write!(f, "{tag}"),
+            AttributeQuery::Not(query) => write!(f, "not {query}"),
+            AttributeQuery::And(queries) => write!(f, "{}", join(queries, " and ")),
+            AttributeQuery::Or(queries) => write!(f, "{}", join(queries, " or ")),
+        }
     }
 }
 
 #[bevy_trait_query::queryable]
 pub trait Attribute: TooltipComponent {
-    fn name(&self) -> &'static str;
+    fn tag(&self) -> AttributeTag;
+    fn name(&self) -> &'static str {
+        self.tag().name()
+    }
     fn _get_tooltip_section(&self) -> TooltipSection {
-        let text = format!("{}", self.name());
-        TooltipSection::default_color(text, TooltipSectionIndex::Footer)
+        TooltipSection::default_color(self.name().to_string(), TooltipSectionIndex::Footer)
     }
 }
 
@@ -35,8 +218,8 @@ pub trait Attribute: TooltipComponent {
 pub struct Pointy;
 
 impl Attribute for Pointy {
-    fn name(&self) -> &'static str {
-        POINTY
+    fn tag(&self) -> AttributeTag {
+        AttributeTag::Pointy
     }
 }
 
@@ -48,22 +231,54 @@ impl TooltipComponent for Pointy {
 
 #[derive(Component, Clone, Debug)]
 pub struct Flintlock {
-    ammo: String,
+    ammo: AmmoKind,
     capacity: usize,
     loaded: usize,
+    /// Percent chance a shot is wasted.
+    misfire: u32,
+    /// Spends a turn loading from neighbouring ammo when empty.
+    reloads: bool,
 }
 
 impl Flintlock {
-    pub fn empty(ammo: String, capacity: usize) -> Self {
+    pub fn empty(ammo: AmmoKind, capacity: usize) -> Self {
         Self {
             ammo,
             capacity,
             loaded: 0,
+            misfire: 0,
+            reloads: false,
         }
     }
 
-    pub fn can_load(&self, ammo: &String) -> bool {
-        ammo.contains(&self.ammo)
+    pub fn with_misfire(mut self, misfire: u32) -> Self {
+        self.misfire = misfire;
+        self
+    }
+
+    pub fn with_reloads(mut self, reloads: bool) -> Self {
+        self.reloads = reloads;
+        self
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn misfire(&self) -> u32 {
+        self.misfire
+    }
+
+    pub fn reloads(&self) -> bool {
+        self.reloads
+    }
+
+    pub fn can_load(&self, ammo: AttributeTag) -> bool {
+        self.ammo.accepts().contains(&ammo)
+    }
+
+    pub fn loaded(&self) -> usize {
+        self.loaded
     }
 
     pub fn load(&mut self, amount: usize) {
@@ -73,7 +288,6 @@ impl Flintlock {
     pub fn fire(&mut self) -> bool {
         if self.loaded == 0 {
             return false;
-        } else {
         }
         self.loaded -= 1;
         true
@@ -81,20 +295,27 @@ impl Flintlock {
 }
 
 impl Attribute for Flintlock {
-    fn name(&self) -> &'static str {
-        FLINTLOCK
+    fn tag(&self) -> AttributeTag {
+        AttributeTag::Flintlock
     }
 }
 
 impl TooltipComponent for Flintlock {
     fn get_tooltip_section(&self) -> TooltipSection {
-        let text = format!(
+        let ammo: Vec<&str> = self.ammo.accepts().iter().map(AttributeTag::name).collect();
+        let mut text = format!(
             "{} ({}/{} {})",
             self.name(),
             self.loaded,
             self.capacity,
-            self.ammo
+            ammo.join("/")
         );
+        if self.misfire > 0 {
+            text.push_str(&format!("\n\t{}% misfire", self.misfire));
+        }
+        if self.reloads {
+            text.push_str("\n\tReloads from neighbours");
+        }
         TooltipSection::default_color(text, TooltipSectionIndex::Footer)
     }
 }
@@ -106,19 +327,18 @@ pub struct Pellets {
 }
 
 impl Attribute for Pellets {
-    fn name(&self) -> &'static str {
-        PELLETS
+    fn tag(&self) -> AttributeTag {
+        AttributeTag::Pellets
     }
 }
 
 impl TooltipComponent for Pellets {
     fn get_tooltip_section(&self) -> TooltipSection {
         let text = format!(
-            "{} (Loads {} [{}] {})",
+            "{} (Loads {} [{}])",
             self.name(),
             self.load_amount,
-            self.target.filter.name(),
-            self.target.attribute,
+            self.target.name(),
         );
         TooltipSection::default_color(text, TooltipSectionIndex::Footer)
     }
@@ -131,20 +351,185 @@ pub struct Cannonball {
 }
 
 impl Attribute for Cannonball {
-    fn name(&self) -> &'static str {
-        CANNONBALL
+    fn tag(&self) -> AttributeTag {
+        AttributeTag::Cannonball
     }
 }
 
 impl TooltipComponent for Cannonball {
     fn get_tooltip_section(&self) -> TooltipSection {
         let text = format!(
-            "{} (Loads {} [{}] {})",
+            "{} (Loads {} [{}])",
             self.name(),
             self.load_amount,
-            self.target.filter.name(),
-            self.target.attribute,
+            self.target.name(),
         );
         TooltipSection::default_color(text, TooltipSectionIndex::Footer)
     }
 }
+
+/// Damage from this item hits every enemy instead of just the target.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Spread;
+
+impl Attribute for Spread {
+    fn tag(&self) -> AttributeTag {
+        AttributeTag::Spread
+    }
+}
+
+impl TooltipComponent for Spread {
+    fn get_tooltip_section(&self) -> TooltipSection {
+        self._get_tooltip_section()
+    }
+}
+
+/// Row of pips along the bottom of a firearm's icon, lit for each loaded shot.
+#[derive(Component)]
+struct AmmoPips;
+
+fn update_ammo_pips(
+    mut commands: Commands,
+    flintlock_q: Query<(Entity, &Flintlock, Option<&Children>), Changed<Flintlock>>,
+    ammo_pips_q: Query<Entity, With<AmmoPips>>,
+) {
+    for (item, flintlock, children) in flintlock_q.iter() {
+        let existing = children
+            .into_iter()
+            .flatten()
+            .find(|&&child| ammo_pips_q.contains(child));
+        let ammo_pips = match existing {
+            Some(&ammo_pips) => ammo_pips,
+            None => {
+                let ammo_pips = commands
+                    .spawn((
+                        AmmoPips,
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                bottom: Val::Px(0.),
+                                left: Val::Px(0.),
+                                column_gap: Val::Px(1.),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ))
+                    .id();
+                commands.entity(item).add_child(ammo_pips);
+                ammo_pips
+            }
+        };
+        commands
+            .entity(ammo_pips)
+            .despawn_descendants()
+            .with_children(|parent| {
+                for shot in 0..flintlock.capacity() {
+                    let color = if shot < flintlock.loaded() {
+                        AMMO_PIP_COLOR
+                    } else {
+                        AMMO_PIP_EMPTY_COLOR
+                    };
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(1.),
+                            height: Val::Px(2.),
+                            ..default()
+                        },
+                        background_color: color.into(),
+                        ..default()
+                    });
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<AttributeQuery, String> {
+        AttributeQuery::try_from(text.to_string())
+    }
+
+    fn has(tag: AttributeTag) -> AttributeQuery {
+        AttributeQuery::Has(QueryTag::Attribute(tag))
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let query = parse("Pointy and not Cursed or Pellets").unwrap();
+        let cursed = AttributeQuery::Has(QueryTag::Ability(AbilityKind::Cursed));
+        assert_eq!(
+            query,
+            AttributeQuery::Or(vec![
+                AttributeQuery::And(vec![
+                    has(AttributeTag::Pointy),
+                    AttributeQuery::Not(Box::new(cursed)),
+                ]),
+                has(AttributeTag::Pellets),
+            ])
+        );
+        assert_eq!(query.to_string(), "Pointy and not Cursed or Pellets");
+    }
+
+    #[test]
+    fn queries_match_the_tags_an_item_has() {
+        let query = parse("Pointy and not Cursed or Pellets").unwrap();
+        let item = |tags: &[QueryTag]| query.matches(&|tag| tags.contains(&tag));
+        let pointy = QueryTag::Attribute(AttributeTag::Pointy);
+        let cursed = QueryTag::Ability(AbilityKind::Cursed);
+        let pellets = QueryTag::Attribute(AttributeTag::Pellets);
+        assert!(item(&[pointy]));
+        assert!(!item(&[pointy, cursed]));
+        assert!(item(&[pellets, cursed]));
+        assert!(!item(&[]));
+    }
+
+    #[test]
+    fn empty_queries_match_anything() {
+        assert_eq!(parse("  "), Ok(AttributeQuery::Any));
+        assert!(AttributeQuery::Any.matches(&|_| false));
+    }
+
+    #[test]
+    fn malformed_queries_are_rejected() {
+        assert!(parse("Pointy and").is_err());
+        assert!(parse("Pointy Cursed").is_err());
+        assert!(parse("not").is_err());
+        assert!(parse("Sharp").is_err());
+    }
+
+    #[test]
+    fn each_flintlock_takes_only_its_own_ammo() {
+        let pistol = Flintlock::empty(AmmoKind::Shot, 1);
+        let cannon = Flintlock::empty(AmmoKind::Ball, 1);
+        assert!(pistol.can_load(AttributeTag::Pellets));
+        assert!(!pistol.can_load(AttributeTag::Cannonball));
+        assert!(cannon.can_load(AttributeTag::Cannonball));
+        assert!(!cannon.can_load(AttributeTag::Pellets));
+    }
+
+    #[test]
+    fn compatibility_table_covers_every_ammo_kind_once() {
+        for kind in [AmmoKind::Shot, AmmoKind::Ball] {
+            let entries = AMMO_COMPATIBILITY.iter().filter(|(k, _)| *k == kind);
+            assert_eq!(entries.count(), 1, "{kind:?}");
+            assert!(!kind.accepts().is_empty(), "{kind:?}");
+            assert!(kind
+                .accepts()
+                .iter()
+                .all(|tag| matches!(tag, AttributeTag::Pellets | AttributeTag::Cannonball)));
+        }
+    }
+
+    #[test]
+    fn tags_match_by_exact_name_only() {
+        for tag in AttributeTag::ALL {
+            assert_eq!(QueryTag::parse(tag.name()), Ok(QueryTag::Attribute(tag)));
+        }
+        for word in ["Pellet", "pellets", "Pelletss", "Ball", "Point"] {
+            assert!(QueryTag::parse(word).is_err(), "{word}");
+        }
+    }
+}
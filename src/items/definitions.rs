@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::{assets::GameSprites, save::SavedItem};
+
+use super::{
+    abilities::{
+        AbilityKind, AbilityTarget, Bleed, Buff, Burn, Cursed, Damage, Hearties, Heave, Jolly,
+        ModifierScope, Poison, SeaLegs, Stun, Swashbuckle, Vitality,
+    },
+    attributes::{AmmoKind, Cannonball, Flintlock, Pellets, Pointy, Spread},
+    Consumable, ItemComponents, ItemType, Rarity,
+};
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum AbilityDefinition {
+    Damage(i32),
+    Hearties(i32),
+    Cursed(i32),
+    Heave(i32, AbilityTarget),
+    SeaLegs(i32),
+    Swashbuckle(i32, AbilityTarget),
+    Jolly(i32, AbilityTarget),
+    Vitality(i32),
+    /// Stacks and duration in turns.
+    Poison(i32, i32),
+    /// Stacks and duration in turns.
+    Bleed(i32, i32),
+    Burn(i32),
+    Stun(i32),
+    /// Raises another ability on the targeted items.
+    Buff(i32, AbilityKind, AbilityTarget),
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum AttributeDefinition {
+    Pointy,
+    Flintlock {
+        ammo: AmmoKind,
+        capacity: usize,
+        #[serde(default)]
+        misfire: u32,
+        #[serde(default)]
+        reloads: bool,
+    },
+    Pellets {
+        load_amount: usize,
+        target: AbilityTarget,
+    },
+    Cannonball {
+        load_amount: usize,
+        target: AbilityTarget,
+    },
+    Spread,
+}
+
+/// One entry of the item definitions file. Items without a `rarity` never
+/// show up as loot.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ItemDefinition {
+    pub id: ItemType,
+    pub name: String,
+    pub icon: usize,
+    #[serde(default)]
+    pub rarity: Option<Rarity>,
+    #[serde(default)]
+    pub abilities: Vec<AbilityDefinition>,
+    #[serde(default)]
+    pub attributes: Vec<AttributeDefinition>,
+    #[serde(default)]
+    pub consumable: Option<i32>,
+    /// How long the item's buffs last, `Battle` unless stated.
+    #[serde(default)]
+    pub scope: Option<ModifierScope>,
+}
+
+impl ItemDefinition {
+    pub fn components(&self) -> ItemComponents {
+        let mut item = ItemComponents::new(self.id.clone());
+        for ability in self.abilities.iter().cloned() {
+            match ability {
+                AbilityDefinition::Damage(base) => item.damage = Some(Damage::new(base)),
+                AbilityDefinition::Hearties(base) => item.hearties = Some(Hearties::new(base)),
+                AbilityDefinition::Cursed(base) => item.cursed = Some(Cursed::new(base)),
+                AbilityDefinition::Heave(base, target) => {
+                    item.heave = Some(Heave::new(base, target))
+                }
+                AbilityDefinition::SeaLegs(base) => item.sea_legs = Some(SeaLegs::new(base)),
+                AbilityDefinition::Swashbuckle(base, target) => {
+                    item.swashbuckle = Some(Swashbuckle::new(base, target))
+                }
+                AbilityDefinition::Jolly(base, target) => {
+                    item.jolly = Some(Jolly::new(base, target))
+                }
+                AbilityDefinition::Vitality(base) => item.vitality = Some(Vitality::new(base)),
+                AbilityDefinition::Poison(base, duration) => {
+                    item.poison = Some(Poison::new(base, duration))
+                }
+                AbilityDefinition::Bleed(base, duration) => {
+                    item.bleed = Some(Bleed::new(base, duration))
+                }
+                AbilityDefinition::Burn(base) => item.burn = Some(Burn::new(base)),
+                AbilityDefinition::Stun(base) => item.stun = Some(Stun::new(base)),
+                AbilityDefinition::Buff(base, ability, target) => {
+                    item.buff = Some(Buff::new(base, ability, target))
+                }
+            }
+        }
+        for attribute in self.attributes.iter().cloned() {
+            match attribute {
+                AttributeDefinition::Pointy => item.pointy = Some(Pointy),
+                AttributeDefinition::Flintlock {
+                    ammo,
+                    capacity,
+                    misfire,
+                    reloads,
+                } => {
+                    item.flintlock = Some(
+                        Flintlock::empty(ammo, capacity)
+                            .with_misfire(misfire)
+                            .with_reloads(reloads),
+                    )
+                }
+                AttributeDefinition::Pellets {
+                    load_amount,
+                    target,
+                } => {
+                    item.pellets = Some(Pellets {
+                        load_amount,
+                        target,
+                    })
+                }
+                AttributeDefinition::Cannonball {
+                    load_amount,
+                    target,
+                } => {
+                    item.cannonball = Some(Cannonball {
+                        load_amount,
+                        target,
+                    })
+                }
+                AttributeDefinition::Spread => item.spread = Some(Spread),
+            }
+        }
+        item.consumable = self.consumable.map(Consumable);
+        let buffs = !item.buff_effects().is_empty() || item.vitality.is_some();
+        item.scope = self.scope.or(buffs.then(ModifierScope::default));
+        item
+    }
+}
+
+/// Every item in the game, loaded from `assets/data/*.items.ron`.
+#[derive(Asset, Resource, TypePath, Deserialize, Clone, Debug)]
+pub struct ItemDefinitions {
+    pub starting_loot: Vec<ItemType>,
+    pub items: Vec<ItemDefinition>,
+}
+
+impl ItemDefinitions {
+    pub fn get(&self, item_type: &ItemType) -> Option<&ItemDefinition> {
+        self.items.iter().find(|item| item.id == *item_type)
+    }
+
+    pub fn components(&self, item_type: &ItemType) -> Option<ItemComponents> {
+        self.get(item_type).map(ItemDefinition::components)
+    }
+
+    /// Rebuilds `item` from its current definition, keeping the uses, ammo
+    /// and modifiers it picked up during the run.
+    pub fn refresh(&self, item: &ItemComponents) -> Option<ItemComponents> {
+        SavedItem::from(item).components(self)
+    }
+
+    pub fn rand_item(&self, rarity: Rarity, rng: &mut impl Rng) -> Option<ItemType> {
+        let items: Vec<&ItemDefinition> = self
+            .items
+            .iter()
+            .filter(|item| item.rarity == Some(rarity))
+            .collect();
+        items.choose(rng).map(|item| item.id.clone())
+    }
+
+    pub fn spawn(
+        &self,
+        item_type: &ItemType,
+        parent: &mut ChildBuilder,
+        game_sprites: &GameSprites,
+    ) {
+        match self.components(item_type) {
+            Some(components) => self.spawn_components(components, parent, game_sprites),
+            None => warn!("No definition for item {item_type}"),
+        }
+    }
+
+    pub fn spawn_components(
+        &self,
+        components: ItemComponents,
+        parent: &mut ChildBuilder,
+        game_sprites: &GameSprites,
+    ) {
+        match self.get(&components.item_type) {
+            Some(definition) => components.spawn(parent, game_sprites, definition),
+            None => warn!("No definition for item {}", components.item_type),
+        }
+    }
+}
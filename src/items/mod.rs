@@ -1,73 +1,59 @@
-use bevy::{ecs::system::EntityCommands, prelude::*, ui::RelativeCursorPosition};
-use rand::Rng;
+use std::fmt;
+
+use bevy::{
+    ecs::{query::QueryData, system::EntityCommands},
+    prelude::*,
+    ui::RelativeCursorPosition,
+};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    assets::GameSprites,
+    assets::{GameData, GameSprites, RonAssetLoader},
     common::Name,
     inventory::Draggable,
     items::{abilities::AbilityPlugin, attributes::AttributePlugin},
-    tooltip::{TooltipComponent, TooltipSection, TooltipSectionIndex, Tooltipable},
+    tooltip::{
+        RefreshTooltips, TooltipComponent, TooltipSection, TooltipSectionIndex, Tooltipable,
+    },
+    AppState,
 };
 
 use self::{
     abilities::{
-        AbilityTarget, Cursed, Damage, Hearties, Heave, Jolly, SeaLegs, Swashbuckle, TargetFilter,
-        Vitality,
+        Ability, AbilityKind, Bleed, Buff, BuffEffect, Burn, Cursed, Damage, Hearties, Heave,
+        Jolly, ModifierScope, Poison, SeaLegs, Stun, Swashbuckle, Vitality,
     },
-    attributes::{Cannonball, Flintlock, Pellets, Pointy, CANNONBALL, FLINTLOCK, PELLETS, POINTY},
+    attributes::{
+        Attribute, AttributeQuery, AttributeTag, Cannonball, Flintlock, Pellets, Pointy, QueryTag,
+        Spread,
+    },
+    definitions::{ItemDefinition, ItemDefinitions},
 };
 pub mod abilities;
 pub mod attributes;
-
-const MUNDANGE_ITEMS: &[ItemType] = &[
-    ItemType::Orange,
-    ItemType::BagOfBeans,
-    ItemType::Grog,
-    ItemType::WoodenSword,
-    ItemType::MurkyBroth,
-];
-
-const SCARCE_ITEMS: &[ItemType] = &[
-    ItemType::IronSword,
-    ItemType::IronCutlass,
-    ItemType::IronAxe,
-    ItemType::Flag,
-    ItemType::Spyglass,
-    ItemType::Blunderbuss,
-    ItemType::Cannon,
-    ItemType::Cannonball,
-];
-
-const PRECIOUS_ITEMS: &[ItemType] = &[
-    ItemType::BlessedSword,
-    ItemType::BlessedCutlass,
-    ItemType::BlessedAxe,
-    ItemType::CursedSword,
-    ItemType::CursedCutlass,
-    ItemType::CursedAxe,
-    ItemType::ChainShot,
-    ItemType::CursedVial,
-    ItemType::VialOfLife,
-    ItemType::VialOfTheSea,
-    ItemType::VialOfTheEarth,
-];
-
-const MYTHIC_ITEMS: &[ItemType] = &[
-    ItemType::JewelOfTheSea,
-    ItemType::JewelOfTheEarth,
-    ItemType::JewelOfLife,
-    ItemType::CursedJewel,
-];
+pub mod definitions;
 
 pub struct ItemPlugin;
 
 impl Plugin for ItemPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((AbilityPlugin, AttributePlugin));
+        app.add_plugins((AbilityPlugin, AttributePlugin))
+            .init_asset::<ItemDefinitions>()
+            .register_asset_loader(RonAssetLoader::<ItemDefinitions>::new(&["items.ron"]))
+            .add_systems(OnEnter(AppState::InitGame), insert_item_definitions)
+            .add_systems(
+                Update,
+                (
+                    reload_item_definitions.run_if(resource_exists::<GameData>),
+                    refresh_items.run_if(resource_exists_and_changed::<ItemDefinitions>),
+                )
+                    .chain()
+                    .run_if(not(in_state(AppState::LoadingAssets))),
+            );
     }
 }
 
-#[derive(Component, Clone, Copy, Debug)]
+#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 pub enum Rarity {
     Mundane,
     Scarce,
@@ -76,15 +62,6 @@ pub enum Rarity {
 }
 
 impl Rarity {
-    pub fn rand_item(&self, rng: &mut crate::rng::Rng) -> ItemType {
-        match self {
-            Rarity::Mundane => MUNDANGE_ITEMS[rng.0.gen_range(0..MUNDANGE_ITEMS.len())],
-            Rarity::Scarce => SCARCE_ITEMS[rng.0.gen_range(0..SCARCE_ITEMS.len())],
-            Rarity::Precious => PRECIOUS_ITEMS[rng.0.gen_range(0..PRECIOUS_ITEMS.len())],
-            Rarity::Mythic => MYTHIC_ITEMS[rng.0.gen_range(0..MYTHIC_ITEMS.len())],
-        }
-    }
-
     fn name(&self) -> String {
         match self {
             Rarity::Mundane => "Mundane".to_string(),
@@ -93,31 +70,23 @@ impl Rarity {
             Rarity::Mythic => "Mythic".to_string(),
         }
     }
-}
 
-impl From<ItemType> for Rarity {
-    fn from(value: ItemType) -> Self {
-        for item in MUNDANGE_ITEMS {
-            if value == *item {
-                return Self::Mundane;
-            }
-        }
-        for item in SCARCE_ITEMS {
-            if value == *item {
-                return Self::Scarce;
-            }
-        }
-        for item in PRECIOUS_ITEMS {
-            if value == *item {
-                return Self::Precious;
-            }
+    /// The next rarity up, staying at `Mythic`.
+    pub fn upgrade(&self) -> Rarity {
+        match self {
+            Rarity::Mundane => Rarity::Scarce,
+            Rarity::Scarce => Rarity::Precious,
+            Rarity::Precious | Rarity::Mythic => Rarity::Mythic,
         }
-        for item in MYTHIC_ITEMS {
-            if value == *item {
-                return Self::Mythic;
-            }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Rarity::Mundane => Color::GRAY,
+            Rarity::Scarce => Color::BLUE,
+            Rarity::Precious => Color::ORANGE_RED,
+            Rarity::Mythic => Color::PURPLE,
         }
-        panic!("Item has no rarity assigned");
     }
 }
 
@@ -126,125 +95,255 @@ impl TooltipComponent for Rarity {
         TooltipSection {
             text: self.name(),
             index: TooltipSectionIndex::Footer,
-            color: match self {
-                Rarity::Mundane => Color::GRAY,
-                Rarity::Scarce => Color::BLUE,
-                Rarity::Precious => Color::ORANGE_RED,
-                Rarity::Mythic => Color::PURPLE,
-            },
+            color: self.color(),
         }
     }
 }
 
-#[derive(Component, PartialEq, Clone, Copy, Debug)]
-pub enum ItemType {
-    WoodenSword,
-    IronSword,
-    BlessedSword,
-    CursedSword,
-    IronCutlass,
-    BlessedCutlass,
-    CursedCutlass,
-    IronAxe,
-    BlessedAxe,
-    CursedAxe,
-    Flag,
-    Spyglass,
-    Grog,
-    JewelOfTheSea,   // + sea legs
-    JewelOfLife,     // + hearties
-    JewelOfTheEarth, // + ??
-    CursedJewel,     // Cursed, + Damage
-    Orange,
-    BagOfBeans,
-    MurkyBroth,
-    Blunderbuss,
-    BagOfPellets,
-    Cannon,
-    Cannonball,
-    ChainShot,
-    CursedVial,
-    VialOfLife,
-    VialOfTheSea,
-    VialOfTheEarth,
-}
+/// Id of an item in the item definitions file.
+#[derive(Component, PartialEq, Eq, Hash, Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ItemType(pub String);
 
 impl ItemType {
-    pub fn image_index(&self) -> usize {
-        match self {
-            ItemType::WoodenSword => 0,
-            ItemType::IronSword => 1,
-            ItemType::BlessedSword => 2,
-            ItemType::CursedSword => 3,
-            ItemType::IronCutlass => 4,
-            ItemType::BlessedCutlass => 5,
-            ItemType::CursedCutlass => 6,
-            ItemType::IronAxe => 10,
-            ItemType::BlessedAxe => 11,
-            ItemType::CursedAxe => 12,
-            ItemType::Flag => 31,
-            ItemType::Spyglass => 23,
-            ItemType::Grog => 33,
-            ItemType::JewelOfTheSea => 36,
-            ItemType::JewelOfLife => 35,
-            ItemType::JewelOfTheEarth => 37,
-            ItemType::CursedJewel => 34,
-            ItemType::Orange => 16,
-            ItemType::BagOfBeans => 17,
-            ItemType::MurkyBroth => 18,
-            ItemType::Blunderbuss => 8,
-            ItemType::BagOfPellets => 9,
-            ItemType::Cannon => 13,
-            ItemType::Cannonball => 14,
-            ItemType::ChainShot => 15,
-            ItemType::CursedVial => 26,
-            ItemType::VialOfLife => 27,
-            ItemType::VialOfTheSea => 28,
-            ItemType::VialOfTheEarth => 29,
+    pub fn new(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl fmt::Display for ItemType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn insert_item_definitions(
+    mut commands: Commands,
+    game_data: Res<GameData>,
+    item_definitions: Res<Assets<ItemDefinitions>>,
+) {
+    match item_definitions.get(&game_data.items) {
+        Some(definitions) => commands.insert_resource(definitions.clone()),
+        None => error!("Item definitions failed to load"),
+    }
+}
+
+fn reload_item_definitions(
+    mut commands: Commands,
+    mut asset_er: EventReader<AssetEvent<ItemDefinitions>>,
+    game_data: Res<GameData>,
+    item_definitions: Res<Assets<ItemDefinitions>>,
+) {
+    for event in asset_er.read() {
+        if !event.is_modified(&game_data.items) {
+            continue;
+        }
+        if let Some(definitions) = item_definitions.get(&game_data.items) {
+            info!("Reloaded item definitions");
+            commands.insert_resource(definitions.clone());
         }
     }
+}
 
-    pub fn name(&self) -> String {
-        match self {
-            ItemType::Grog => "Grog".to_string(),
-            ItemType::WoodenSword => "Wooden Sword".to_string(),
-            ItemType::IronSword => "Iron Sword".to_string(),
-            ItemType::BlessedSword => "Blessed Sword".to_string(),
-            ItemType::CursedSword => "Cursed Sword".to_string(),
-            ItemType::IronCutlass => "Iron Cutlass".to_string(),
-            ItemType::BlessedCutlass => "Blessed Cutlass".to_string(),
-            ItemType::CursedCutlass => "Cursed Cutlass".to_string(),
-            ItemType::IronAxe => "Iron Axe".to_string(),
-            ItemType::BlessedAxe => "Blessed Axe".to_string(),
-            ItemType::CursedAxe => "Cursed Axe".to_string(),
-            ItemType::Flag => "Flag".to_string(),
-            ItemType::Spyglass => "Spyglass".to_string(),
-            ItemType::JewelOfTheSea => "Jewel of the Sea".to_string(),
-            ItemType::JewelOfLife => "Jewel of Life".to_string(),
-            ItemType::JewelOfTheEarth => "Jewel of the Earth".to_string(),
-            ItemType::CursedJewel => "Cursed Jewel".to_string(),
-            ItemType::Orange => "Orange".to_string(),
-            ItemType::BagOfBeans => "Bag Of Beans".to_string(),
-            ItemType::MurkyBroth => "Murky Broth".to_string(),
-            ItemType::Blunderbuss => "Blunderbuss".to_string(),
-            ItemType::BagOfPellets => "Bag Of Pellets".to_string(),
-            ItemType::Cannon => "Cannon".to_string(),
-            ItemType::Cannonball => "Cannonball".to_string(),
-            ItemType::ChainShot => "Chain Shot".to_string(),
-            ItemType::CursedVial => "Cursed Vial".to_string(),
-            ItemType::VialOfLife => "Vial Of Life".to_string(),
-            ItemType::VialOfTheSea => "Vial Of The Sea".to_string(),
-            ItemType::VialOfTheEarth => "Vial Of The Earth".to_string(),
+fn refresh_items(
+    mut commands: Commands,
+    mut refresh_tooltips_ew: EventWriter<RefreshTooltips>,
+    item_definitions: Res<ItemDefinitions>,
+    mut items_q: Query<(
+        Entity,
+        ItemComponentsQueryReadOnly,
+        &mut Name,
+        &mut TextureAtlas,
+    )>,
+) {
+    if items_q.is_empty() {
+        return;
+    }
+    for (entity, item, mut name, mut texture_atlas) in items_q.iter_mut() {
+        let Some(definition) = item_definitions.get(item.item_type) else {
+            continue;
+        };
+        let Some(components) = item_definitions.refresh(&item.components()) else {
+            continue;
+        };
+        name.0 = definition.name.clone();
+        texture_atlas.index = definition.icon;
+
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.remove::<ItemComponentsBundle>();
+        if let Some(rarity) = definition.rarity {
+            entity_commands.insert(rarity);
+        }
+        components.insert(&mut entity_commands);
+    }
+    refresh_tooltips_ew.send(RefreshTooltips);
+}
+
+/// Plain-data copy of everything an item carries into battle.
+#[derive(Clone, Debug)]
+pub struct ItemComponents {
+    pub item_type: ItemType,
+    pub damage: Option<Damage>,
+    pub hearties: Option<Hearties>,
+    pub cursed: Option<Cursed>,
+    pub heave: Option<Heave>,
+    pub sea_legs: Option<SeaLegs>,
+    pub swashbuckle: Option<Swashbuckle>,
+    pub jolly: Option<Jolly>,
+    pub vitality: Option<Vitality>,
+    pub poison: Option<Poison>,
+    pub bleed: Option<Bleed>,
+    pub burn: Option<Burn>,
+    pub stun: Option<Stun>,
+    pub buff: Option<Buff>,
+    pub pointy: Option<Pointy>,
+    pub flintlock: Option<Flintlock>,
+    pub pellets: Option<Pellets>,
+    pub cannonball: Option<Cannonball>,
+    pub spread: Option<Spread>,
+    pub consumable: Option<Consumable>,
+    pub scope: Option<ModifierScope>,
+}
+
+impl ItemComponents {
+    pub fn new(item_type: ItemType) -> Self {
+        Self {
+            item_type,
+            damage: None,
+            hearties: None,
+            cursed: None,
+            heave: None,
+            sea_legs: None,
+            swashbuckle: None,
+            jolly: None,
+            vitality: None,
+            poison: None,
+            bleed: None,
+            burn: None,
+            stun: None,
+            buff: None,
+            pointy: None,
+            flintlock: None,
+            pellets: None,
+            cannonball: None,
+            spread: None,
+            consumable: None,
+            scope: None,
+        }
+    }
+
+    pub fn ability(&self, kind: AbilityKind) -> Option<&dyn Ability> {
+        match kind {
+            AbilityKind::Damage => self.damage.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::Hearties => self.hearties.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::Cursed => self.cursed.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::Heave => self.heave.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::SeaLegs => self.sea_legs.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::Swashbuckle => self.swashbuckle.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::Jolly => self.jolly.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::Vitality => self.vitality.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::Poison => self.poison.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::Bleed => self.bleed.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::Burn => self.burn.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::Stun => self.stun.as_ref().map(|a| a as &dyn Ability),
+            AbilityKind::Buff => self.buff.as_ref().map(|a| a as &dyn Ability),
+        }
+    }
+
+    pub fn ability_mut(&mut self, kind: AbilityKind) -> Option<&mut dyn Ability> {
+        match kind {
+            AbilityKind::Damage => self.damage.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::Hearties => self.hearties.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::Cursed => self.cursed.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::Heave => self.heave.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::SeaLegs => self.sea_legs.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::Swashbuckle => self.swashbuckle.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::Jolly => self.jolly.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::Vitality => self.vitality.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::Poison => self.poison.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::Bleed => self.bleed.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::Burn => self.burn.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::Stun => self.stun.as_mut().map(|a| a as &mut dyn Ability),
+            AbilityKind::Buff => self.buff.as_mut().map(|a| a as &mut dyn Ability),
+        }
+    }
+
+    /// Every buff the item hands out when used.
+    pub fn buff_effects(&self) -> Vec<BuffEffect> {
+        let heave = self.heave.as_ref().map(Heave::effect);
+        let swashbuckle = self.swashbuckle.as_ref().map(Swashbuckle::effect);
+        let jolly = self.jolly.as_ref().map(Jolly::effect);
+        let buff = self.buff.as_ref().map(Buff::effect);
+        [heave, swashbuckle, jolly, buff]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Drops every buff on the item lasting no longer than `scope`.
+    pub fn expire_modifiers(&mut self, scope: ModifierScope) {
+        for kind in AbilityKind::ALL {
+            if let Some(ability) = self.ability_mut(kind) {
+                ability.modifier_mut().expire(scope);
+            }
+        }
+        // Curses a boss laid on the item lift with their modifiers.
+        if self
+            .cursed
+            .as_ref()
+            .is_some_and(|c| c.base == 0 && c.amount() == 0)
+        {
+            self.cursed = None;
+        }
+    }
+
+    pub fn attributes(&self) -> Vec<&dyn Attribute> {
+        let mut attributes: Vec<&dyn Attribute> = Vec::new();
+        if let Some(pointy) = &self.pointy {
+            attributes.push(pointy);
+        }
+        if let Some(flintlock) = &self.flintlock {
+            attributes.push(flintlock);
+        }
+        if let Some(pellets) = &self.pellets {
+            attributes.push(pellets);
         }
+        if let Some(cannonball) = &self.cannonball {
+            attributes.push(cannonball);
+        }
+        if let Some(spread) = &self.spread {
+            attributes.push(spread);
+        }
+        attributes
+    }
+
+    pub fn has_attribute(&self, tag: AttributeTag) -> bool {
+        self.attributes().iter().any(|a| a.tag() == tag)
     }
 
-    pub fn spawn(&self, parent: &mut ChildBuilder, game_sprites: &GameSprites) {
+    pub fn has(&self, tag: QueryTag) -> bool {
+        match tag {
+            QueryTag::Attribute(tag) => self.has_attribute(tag),
+            QueryTag::Ability(kind) => self.ability(kind).is_some(),
+        }
+    }
+
+    pub fn matches(&self, query: &AttributeQuery) -> bool {
+        query.matches(&|tag| self.has(tag))
+    }
+
+    pub fn spawn(
+        self,
+        parent: &mut ChildBuilder,
+        game_sprites: &GameSprites,
+        definition: &ItemDefinition,
+    ) {
         let bundle = (
             AtlasImageBundle {
                 image: UiImage::new(game_sprites.items_tile_sheet.clone()),
                 texture_atlas: TextureAtlas {
                     layout: game_sprites.items_tile_layout.clone(),
-                    index: self.image_index(),
+                    index: definition.icon,
                 },
                 style: Style {
                     width: Val::Px(16.),
@@ -253,178 +352,193 @@ impl ItemType {
                 },
                 ..default()
             },
-            Name(self.name()),
-            Rarity::from(*self),
+            Name(definition.name.clone()),
             Draggable,
             RelativeCursorPosition::default(),
             Tooltipable::default(),
         );
-        let entity_commands = parent.spawn(bundle);
-        self.insert(entity_commands);
+        let mut entity_commands = parent.spawn(bundle);
+        if let Some(rarity) = definition.rarity {
+            entity_commands.insert(rarity);
+        }
+        self.insert(&mut entity_commands);
     }
 
-    fn insert(&self, mut entity_commands: EntityCommands) {
-        match self {
-            ItemType::WoodenSword => entity_commands.insert((Damage::new(3), Pointy)),
-            ItemType::IronSword => entity_commands.insert((
-                Damage::new(5),
-                Heave::new(
-                    1,
-                    AbilityTarget {
-                        filter: TargetFilter::Next(1),
-                        attribute: POINTY.to_string(),
-                    },
-                ),
-                Pointy,
-            )),
-            ItemType::BlessedSword => entity_commands.insert((
-                Damage::new(4),
-                Heave::new(
-                    1,
-                    AbilityTarget {
-                        filter: TargetFilter::Next(1),
-                        attribute: POINTY.to_string(),
-                    },
-                ),
-                Hearties::new(3),
-                Pointy,
-            )),
-            ItemType::CursedSword => entity_commands.insert((
-                Damage::new(8),
-                Heave::new(
-                    2,
-                    AbilityTarget {
-                        filter: TargetFilter::Next(1),
-                        attribute: POINTY.to_string(),
-                    },
-                ),
-                Cursed::new(2),
-                Pointy,
-            )),
-            ItemType::IronCutlass => {
-                entity_commands.insert((Damage::new(3), SeaLegs::new(1), Pointy))
-            }
-            ItemType::BlessedCutlass => {
-                entity_commands.insert((Damage::new(2), SeaLegs::new(1), Hearties::new(2), Pointy))
-            }
-            ItemType::CursedCutlass => {
-                entity_commands.insert((Damage::new(5), SeaLegs::new(2), Cursed::new(2), Pointy))
-            }
-            ItemType::IronAxe => entity_commands.insert(Damage::new(7)),
-            ItemType::BlessedAxe => entity_commands.insert((Damage::new(6), Hearties::new(2))),
-            ItemType::CursedAxe => entity_commands.insert((Damage::new(11), Cursed::new(2))),
-            ItemType::Flag => entity_commands.insert((Heave::new(
-                2,
-                AbilityTarget {
-                    filter: TargetFilter::Neighbours,
-                    attribute: POINTY.to_string(),
-                },
-            ),)),
-            ItemType::Spyglass => entity_commands.insert((Heave::new(
-                2,
-                AbilityTarget {
-                    filter: TargetFilter::Next(2),
-                    attribute: FLINTLOCK.to_string(),
-                },
-            ),)),
-            ItemType::Grog => entity_commands.insert((SeaLegs::new(3), Consumable(3))),
-            ItemType::JewelOfTheSea => entity_commands.insert((Swashbuckle::new(
-                2,
-                AbilityTarget::with_all_attributes(TargetFilter::All),
-            ),)),
-            ItemType::JewelOfLife => entity_commands.insert((Jolly::new(
-                2,
-                AbilityTarget::with_all_attributes(TargetFilter::All),
-            ),)),
-            ItemType::JewelOfTheEarth => {
-                entity_commands.insert((Vitality::new(4), Hearties::new(4)))
-            }
-            ItemType::CursedJewel => entity_commands.insert((
-                Cursed::new(1),
-                Heave::new(2, AbilityTarget::with_all_attributes(TargetFilter::All)),
-            )),
-            ItemType::Orange => entity_commands.insert((
-                Hearties::new(7),
-                Vitality::new(2),
-                Cannonball {
-                    load_amount: 1,
-                    target: AbilityTarget {
-                        filter: TargetFilter::Next(1),
-                        attribute: FLINTLOCK.to_string(),
-                    },
-                },
-                Consumable(1),
-            )),
-            ItemType::BagOfBeans => entity_commands.insert((
-                Hearties::new(5),
-                Vitality::new(1),
-                Pellets {
-                    load_amount: 4,
-                    target: AbilityTarget {
-                        filter: TargetFilter::Next(1),
-                        attribute: FLINTLOCK.to_string(),
-                    },
-                },
-                Consumable(2),
-            )),
-            ItemType::MurkyBroth => entity_commands.insert((
-                Cursed::new(1),
-                Vitality::new(5),
-                SeaLegs::new(2),
-                Consumable(1),
-            )),
-            ItemType::Blunderbuss => {
-                entity_commands.insert((Flintlock::empty(PELLETS.to_string(), 8), Damage::new(15)))
-            }
-            ItemType::BagOfPellets => entity_commands.insert((
-                Pellets {
-                    load_amount: 4,
-                    target: AbilityTarget {
-                        filter: TargetFilter::AllNext,
-                        attribute: FLINTLOCK.to_string(),
-                    },
-                },
-                Consumable(6),
-            )),
-            ItemType::Cannon => entity_commands
-                .insert((Flintlock::empty(CANNONBALL.to_string(), 2), Damage::new(27))),
-            ItemType::Cannonball => entity_commands.insert((
-                Cannonball {
-                    load_amount: 1,
-                    target: AbilityTarget {
-                        filter: TargetFilter::AllNext,
-                        attribute: FLINTLOCK.to_string(),
-                    },
-                },
-                Consumable(1),
-            )),
-            ItemType::ChainShot => entity_commands.insert((
-                Cannonball {
-                    load_amount: 2,
-                    target: AbilityTarget {
-                        filter: TargetFilter::AllNext,
-                        attribute: FLINTLOCK.to_string(),
-                    },
-                },
-                Consumable(1),
-            )),
-            ItemType::CursedVial => entity_commands.insert((
-                Cursed::new(3),
-                Heave::new(7, AbilityTarget::with_all_attributes(TargetFilter::All)),
-                Consumable(1),
-            )),
-            ItemType::VialOfLife => entity_commands.insert((
-                Jolly::new(6, AbilityTarget::with_all_attributes(TargetFilter::All)),
-                Consumable(1),
-            )),
-            ItemType::VialOfTheSea => entity_commands.insert((
-                Swashbuckle::new(6, AbilityTarget::with_all_attributes(TargetFilter::All)),
-                Consumable(1),
-            )),
-            ItemType::VialOfTheEarth => {
-                entity_commands.insert((Vitality::new(6), Hearties::new(6), Consumable(1)))
+    pub fn insert(self, entity_commands: &mut EntityCommands) {
+        entity_commands.insert(self.item_type);
+        if let Some(damage) = self.damage {
+            entity_commands.insert(damage);
+        }
+        if let Some(hearties) = self.hearties {
+            entity_commands.insert(hearties);
+        }
+        if let Some(cursed) = self.cursed {
+            entity_commands.insert(cursed);
+        }
+        if let Some(heave) = self.heave {
+            entity_commands.insert(heave);
+        }
+        if let Some(sea_legs) = self.sea_legs {
+            entity_commands.insert(sea_legs);
+        }
+        if let Some(swashbuckle) = self.swashbuckle {
+            entity_commands.insert(swashbuckle);
+        }
+        if let Some(jolly) = self.jolly {
+            entity_commands.insert(jolly);
+        }
+        if let Some(vitality) = self.vitality {
+            entity_commands.insert(vitality);
+        }
+        if let Some(poison) = self.poison {
+            entity_commands.insert(poison);
+        }
+        if let Some(bleed) = self.bleed {
+            entity_commands.insert(bleed);
+        }
+        if let Some(burn) = self.burn {
+            entity_commands.insert(burn);
+        }
+        if let Some(stun) = self.stun {
+            entity_commands.insert(stun);
+        }
+        if let Some(buff) = self.buff {
+            entity_commands.insert(buff);
+        }
+        if let Some(pointy) = self.pointy {
+            entity_commands.insert(pointy);
+        }
+        if let Some(flintlock) = self.flintlock {
+            entity_commands.insert(flintlock);
+        }
+        if let Some(pellets) = self.pellets {
+            entity_commands.insert(pellets);
+        }
+        if let Some(cannonball) = self.cannonball {
+            entity_commands.insert(cannonball);
+        }
+        if let Some(spread) = self.spread {
+            entity_commands.insert(spread);
+        }
+        if let Some(consumable) = self.consumable {
+            entity_commands.insert(consumable);
+        }
+        if let Some(scope) = self.scope {
+            entity_commands.insert(scope);
+        }
+    }
+}
+
+/// Every optional component an item can carry, for removing them all at once.
+type ItemComponentsBundle = (
+    (
+        Rarity,
+        Damage,
+        Hearties,
+        Cursed,
+        Heave,
+        SeaLegs,
+        Swashbuckle,
+        Jolly,
+        Vitality,
+        Poison,
+        Bleed,
+        Burn,
+        Stun,
+        Buff,
+    ),
+    (
+        Pointy,
+        Flintlock,
+        Pellets,
+        Cannonball,
+        Spread,
+        Consumable,
+        ModifierScope,
+    ),
+);
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct ItemComponentsQuery {
+    pub item_type: &'static ItemType,
+    pub damage: Option<&'static mut Damage>,
+    pub hearties: Option<&'static mut Hearties>,
+    pub cursed: Option<&'static mut Cursed>,
+    pub heave: Option<&'static mut Heave>,
+    pub sea_legs: Option<&'static mut SeaLegs>,
+    pub swashbuckle: Option<&'static mut Swashbuckle>,
+    pub jolly: Option<&'static mut Jolly>,
+    pub vitality: Option<&'static mut Vitality>,
+    pub poison: Option<&'static mut Poison>,
+    pub bleed: Option<&'static mut Bleed>,
+    pub burn: Option<&'static mut Burn>,
+    pub stun: Option<&'static mut Stun>,
+    pub buff: Option<&'static mut Buff>,
+    pub pointy: Option<&'static mut Pointy>,
+    pub flintlock: Option<&'static mut Flintlock>,
+    pub pellets: Option<&'static mut Pellets>,
+    pub cannonball: Option<&'static mut Cannonball>,
+    pub spread: Option<&'static mut Spread>,
+    pub consumable: Option<&'static mut Consumable>,
+    pub scope: Option<&'static mut ModifierScope>,
+}
+
+impl ItemComponentsQueryReadOnlyItem<'_> {
+    pub fn components(&self) -> ItemComponents {
+        ItemComponents {
+            item_type: self.item_type.clone(),
+            damage: self.damage.cloned(),
+            hearties: self.hearties.cloned(),
+            cursed: self.cursed.cloned(),
+            heave: self.heave.cloned(),
+            sea_legs: self.sea_legs.cloned(),
+            swashbuckle: self.swashbuckle.cloned(),
+            jolly: self.jolly.cloned(),
+            vitality: self.vitality.cloned(),
+            poison: self.poison.cloned(),
+            bleed: self.bleed.cloned(),
+            burn: self.burn.cloned(),
+            stun: self.stun.cloned(),
+            buff: self.buff.cloned(),
+            pointy: self.pointy.cloned(),
+            flintlock: self.flintlock.cloned(),
+            pellets: self.pellets.cloned(),
+            cannonball: self.cannonball.cloned(),
+            spread: self.spread.cloned(),
+            consumable: self.consumable.cloned(),
+            scope: self.scope.cloned(),
+        }
+    }
+}
+
+impl ItemComponentsQueryItem<'_> {
+    pub fn set(&mut self, components: &ItemComponents) {
+        fn set<T: Component + Clone>(target: &mut Option<Mut<T>>, value: &Option<T>) {
+            if let (Some(target), Some(value)) = (target, value) {
+                **target = value.clone();
             }
-        };
+        }
+        set(&mut self.damage, &components.damage);
+        set(&mut self.hearties, &components.hearties);
+        set(&mut self.cursed, &components.cursed);
+        set(&mut self.heave, &components.heave);
+        set(&mut self.sea_legs, &components.sea_legs);
+        set(&mut self.swashbuckle, &components.swashbuckle);
+        set(&mut self.jolly, &components.jolly);
+        set(&mut self.vitality, &components.vitality);
+        set(&mut self.poison, &components.poison);
+        set(&mut self.bleed, &components.bleed);
+        set(&mut self.burn, &components.burn);
+        set(&mut self.stun, &components.stun);
+        set(&mut self.buff, &components.buff);
+        set(&mut self.pointy, &components.pointy);
+        set(&mut self.flintlock, &components.flintlock);
+        set(&mut self.pellets, &components.pellets);
+        set(&mut self.cannonball, &components.cannonball);
+        set(&mut self.spread, &components.spread);
+        set(&mut self.consumable, &components.consumable);
+        set(&mut self.scope, &components.scope);
     }
 }
 
@@ -434,7 +548,7 @@ pub trait Item {
     fn add_bundle(&self, entity_commands: &mut EntityCommands);
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Debug)]
 pub struct Consumable(pub i32);
 
 impl TooltipComponent for Consumable {
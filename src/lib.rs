@@ -0,0 +1,86 @@
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+pub mod assets;
+pub mod balance;
+pub mod battle;
+pub mod common;
+pub mod enemy;
+pub mod inventory;
+pub mod items;
+pub mod log;
+pub mod music;
+pub mod numoids;
+pub mod player;
+pub mod replay;
+pub mod rng;
+pub mod save;
+pub mod scene;
+pub mod solver;
+pub mod status;
+pub mod tooltip;
+pub mod ui;
+
+use bevy::prelude::*;
+
+use crate::{
+    battle::BattlePlugin, enemy::EnemyPlugin, inventory::InventoryPlugin, items::ItemPlugin,
+    log::BattleLogPlugin, music::MusicPlugin, numoids::NumoidPlugin, player::PlayerPlugin,
+    replay::ReplayPlugin, rng::RngPlugin, save::SavePlugin, scene::ScenePlugin,
+    status::StatusPlugin, tooltip::TooltipPlugin, ui::UIPlugin,
+};
+
+pub const GAME_WIDTH: f32 = 320.;
+pub const GAME_HEIGHT: f32 = 180.;
+
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AppState {
+    #[default]
+    LoadingAssets,
+    InitGame,
+    GameStart,
+    OrganizeInventory,
+    Battling,
+    GameOver,
+}
+
+#[derive(Resource, Default)]
+pub struct BattleWins(pub usize);
+
+/// All of the game's own plugins and the state flow between them. Rendering,
+/// windowing and asset loading are left to the app that adds it.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<AppState>()
+            .add_plugins(RngPlugin)
+            .add_plugins(ReplayPlugin)
+            .add_plugins(SavePlugin)
+            .add_plugins(UIPlugin)
+            .add_plugins(ScenePlugin)
+            .add_plugins(MusicPlugin)
+            .add_plugins(PlayerPlugin)
+            .add_plugins(EnemyPlugin)
+            .add_plugins(ItemPlugin)
+            .add_plugins(InventoryPlugin)
+            .add_plugins(BattlePlugin)
+            .add_plugins(StatusPlugin)
+            .add_plugins(BattleLogPlugin)
+            .add_plugins(TooltipPlugin)
+            .add_plugins(NumoidPlugin)
+            .add_systems(OnEnter(AppState::InitGame), setup_scene)
+            .add_systems(OnEnter(AppState::GameStart), reset_battle_wins);
+    }
+}
+
+fn setup_scene(mut commands: Commands, mut next_app_state: ResMut<NextState<AppState>>) {
+    commands.spawn(Camera2dBundle {
+        ..Default::default()
+    });
+
+    next_app_state.set(AppState::GameStart);
+}
+
+fn reset_battle_wins(mut commands: Commands) {
+    commands.insert_resource(BattleWins::default());
+}
@@ -1,61 +1,427 @@
-use bevy::prelude::*;
+use std::collections::HashMap;
 
-use crate::{assets::GameFonts, AppState};
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    ui::RelativeCursorPosition,
+};
+
+use crate::{
+    assets::GameFonts,
+    battle::{sim::EffectStat, sim::Side, ActiveBattle, BattleEffect, BattleEvent},
+    common::{Hp, Name},
+    enemy::{EnemyArchetype, EnemySlot},
+    items::Rarity,
+    player::Player,
+    AppState,
+};
 
 const FONT_SIZE: f32 = 6.;
 const FONT_COLOR: Color = Color::WHITE;
+const HIDDEN_COLOR: Color = Color::DARK_GRAY;
 
-const MAX_MESSAGES: usize = 5;
+const LOG_WIDTH: f32 = 100.;
+const LOG_HEIGHT: f32 = 60.;
+const SCROLL_SPEED: f32 = 8.;
 
 pub struct BattleLogPlugin;
 
 impl Plugin for BattleLogPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<LogMessageEvent>()
-            .add_systems(OnEnter(AppState::Battling), setup_log)
+            .init_resource::<BattleLog>()
+            .init_resource::<LogSettings>()
+            .add_systems(OnEnter(AppState::Battling), (clear_battle_log, setup_log))
             .add_systems(OnExit(AppState::Battling), destroy_log)
+            .add_systems(OnEnter(AppState::GameOver), setup_log)
+            .add_systems(OnExit(AppState::GameOver), destroy_log)
+            .add_systems(
+                Update,
+                (
+                    record_battle_names.run_if(resource_added::<ActiveBattle>),
+                    handle_battle_events,
+                    handle_log_message_events,
+                )
+                    .chain(),
+            )
             .add_systems(
                 Update,
-                (handle_log_message_events, cleanup_logs)
+                (log_button_system, refresh_log, scroll_log)
                     .chain()
-                    .run_if(in_state(AppState::Battling)),
+                    .after(handle_log_message_events)
+                    .run_if(any_with_component::<LogRoot>),
             );
     }
 }
 
+/// Adds a plain line to the battle log, shown whatever the filters.
 #[derive(Event)]
 pub struct LogMessageEvent(pub String);
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogCategory {
+    Player,
+    Enemy,
+    Buff,
+}
+
+impl LogCategory {
+    pub const ALL: [LogCategory; 3] = [LogCategory::Player, LogCategory::Enemy, LogCategory::Buff];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogCategory::Player => "Player",
+            LogCategory::Enemy => "Enemy",
+            LogCategory::Buff => "Buffs",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub category: Option<LogCategory>,
+    pub sections: Vec<(String, Color)>,
+}
+
+impl LogEntry {
+    fn new(category: Option<LogCategory>) -> Self {
+        Self {
+            category,
+            sections: Vec::new(),
+        }
+    }
+
+    fn text(mut self, text: impl Into<String>) -> Self {
+        self.sections.push((text.into(), FONT_COLOR));
+        self
+    }
+
+    fn name(mut self, (name, color): (String, Color)) -> Self {
+        self.sections.push((name, color));
+        self
+    }
+
+    pub fn text_value(&self) -> String {
+        self.sections
+            .iter()
+            .map(|(text, _)| text.as_str())
+            .collect()
+    }
+}
+
+/// Everything that happened in the current or last battle.
+#[derive(Resource, Default)]
+pub struct BattleLog {
+    pub entries: Vec<LogEntry>,
+    /// Names of the player, enemies and items, kept so consumed items can
+    /// still be named after they are despawned.
+    names: HashMap<Entity, (String, Color)>,
+    player: Option<Entity>,
+    enemies: Vec<String>,
+}
+
+impl BattleLog {
+    fn name(&self, entity: Entity) -> (String, Color) {
+        self.names
+            .get(&entity)
+            .cloned()
+            .unwrap_or_else(|| ("Something".to_string(), FONT_COLOR))
+    }
+
+    fn side_name(&self, side: Side) -> (String, Color) {
+        match side {
+            Side::Player => ("Player".to_string(), FONT_COLOR),
+            Side::Enemy(index) => (
+                self.enemies
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(|| "Enemy".to_string()),
+                FONT_COLOR,
+            ),
+        }
+    }
+
+    fn side_category(side: Side) -> Option<LogCategory> {
+        match side {
+            Side::Player => Some(LogCategory::Player),
+            Side::Enemy(_) => Some(LogCategory::Enemy),
+        }
+    }
+
+    fn effect_entry(&self, effect: &BattleEffect) -> Option<LogEntry> {
+        let category = match effect.stat {
+            EffectStat::MaxHp
+            | EffectStat::SeaLegs
+            | EffectStat::Armor
+            | EffectStat::Ability(_) => LogCategory::Buff,
+            _ if Some(effect.source) == self.player => LogCategory::Player,
+            _ => LogCategory::Enemy,
+        };
+        let change = effect.after - effect.before;
+        if change == 0 {
+            return None;
+        }
+        let item = effect.item.map(|item| self.name(item));
+        let target = self.name(effect.target);
+        let entry = LogEntry::new(Some(category));
+        let entry = match effect.stat {
+            EffectStat::Hp if change < 0 => match (item, effect.ability) {
+                (Some(item), _) => entry
+                    .name(item)
+                    .text(" hits ")
+                    .name(target)
+                    .text(format!(" for {}", -change)),
+                (None, Some(ability)) => {
+                    entry
+                        .name(target)
+                        .text(format!(" takes {} {}", -change, ability.name()))
+                }
+                (None, None) => entry.name(target).text(format!(" takes {}", -change)),
+            },
+            EffectStat::Hp => {
+                let entry = entry.name(target).text(format!(" heals {change}"));
+                match item {
+                    Some(item) => entry.text(" from ").name(item),
+                    None => entry,
+                }
+            }
+            EffectStat::MaxHp => entry.name(target).text(format!(" max hp {:+}", change)),
+            EffectStat::SeaLegs => entry.name(target).text(format!(" sea legs {:+}", change)),
+            EffectStat::Armor => entry.name(target).text(format!(" armor {:+}", change)),
+            EffectStat::Ability(ability) => {
+                let entry = match item {
+                    Some(item) if effect.item != Some(effect.target) => {
+                        entry.name(item).text(" gives ")
+                    }
+                    _ => entry,
+                };
+                entry
+                    .name(target)
+                    .text(format!(" {:+} {}", change, ability.name()))
+            }
+            EffectStat::Ammo if change < 0 => entry.name(target).text(" fires"),
+            EffectStat::Ammo => {
+                let entry = match item {
+                    Some(item) if effect.item != Some(effect.target) => {
+                        entry.name(item).text(" loads ")
+                    }
+                    _ => entry.text("Reloaded "),
+                };
+                entry.name(target).text(format!(" +{change}"))
+            }
+            EffectStat::Uses if effect.after == 0 => entry.name(target).text(" is used up"),
+            EffectStat::Uses => return None,
+            EffectStat::Status(status) if change > 0 => {
+                let entry = match item {
+                    Some(item) => entry.name(item).text(" gives "),
+                    None => entry,
+                };
+                entry
+                    .name(target)
+                    .text(format!(" {} {}", change, status.name()))
+            }
+            EffectStat::Status(status) => {
+                entry
+                    .name(target)
+                    .text(format!(" {} {}", status.name(), effect.after))
+            }
+        };
+        Some(entry)
+    }
+
+    fn event_entry(&self, event: &BattleEvent) -> Option<LogEntry> {
+        let side_entry = |side: Side, text: &str| {
+            LogEntry::new(Self::side_category(side))
+                .name(self.side_name(side))
+                .text(text)
+        };
+        match event {
+            BattleEvent::Effect(effect) => self.effect_entry(effect),
+            BattleEvent::EnemyAttack(enemy) => Some(side_entry(Side::Enemy(*enemy), " attacks")),
+            BattleEvent::Click(side) => Some(side_entry(*side, " clicks")),
+            BattleEvent::Misfire(side) => Some(side_entry(*side, " misfires")),
+            BattleEvent::Reload(side) => Some(side_entry(*side, " reloads")),
+            BattleEvent::Stunned(side) => Some(side_entry(*side, " is stunned")),
+            BattleEvent::PlayerHurt(_)
+            | BattleEvent::PlayerHeal(_)
+            | BattleEvent::EnemyHurt(..)
+            | BattleEvent::EnemyHeal(..) => None,
+        }
+    }
+}
+
+/// How the log panel is shown. Kept across battles.
+#[derive(Resource, Default)]
+pub struct LogSettings {
+    pub collapsed: bool,
+    pub hidden: Vec<LogCategory>,
+    /// How far the history is scrolled up from the newest line, in pixels.
+    scroll: f32,
+}
+
+impl LogSettings {
+    pub fn shows(&self, entry: &LogEntry) -> bool {
+        entry
+            .category
+            .is_none_or(|category| !self.hidden.contains(&category))
+    }
+
+    fn toggle(&mut self, category: LogCategory) {
+        if let Some(index) = self.hidden.iter().position(|&c| c == category) {
+            self.hidden.remove(index);
+        } else {
+            self.hidden.push(category);
+        }
+    }
+}
+
 #[derive(Component)]
-struct LogMessage;
+pub struct LogMessage;
 
 #[derive(Component)]
 struct LogRoot;
 
+#[derive(Component)]
+struct LogView;
+
+#[derive(Component)]
+struct LogList;
+
+#[derive(Component, Clone, Copy)]
+enum LogButton {
+    Collapse,
+    Filter(LogCategory),
+}
+
+fn clear_battle_log(mut log: ResMut<BattleLog>, mut settings: ResMut<LogSettings>) {
+    log.entries.clear();
+    settings.scroll = 0.;
+}
+
+fn record_battle_names(
+    mut log: ResMut<BattleLog>,
+    names_q: Query<(Entity, &Name, Option<&Rarity>)>,
+    enemy_q: Query<(Entity, &EnemyArchetype, &EnemySlot)>,
+    player_q: Query<Entity, (With<Player>, With<Hp>)>,
+) {
+    log.names = names_q
+        .iter()
+        .map(|(entity, name, rarity)| {
+            let color = rarity.map_or(FONT_COLOR, Rarity::color);
+            (entity, (name.0.clone(), color))
+        })
+        .collect();
+    let mut enemies: Vec<_> = enemy_q.iter().collect();
+    enemies.sort_by_key(|(.., slot)| slot.0);
+    for (entity, archetype, _) in enemies.iter() {
+        log.names
+            .insert(*entity, (archetype.name.clone(), FONT_COLOR));
+    }
+    log.enemies = enemies
+        .into_iter()
+        .map(|(_, archetype, _)| archetype.name.clone())
+        .collect();
+    log.player = player_q.get_single().ok();
+    if let Some(player) = log.player {
+        let name = log.side_name(Side::Player);
+        log.names.insert(player, name);
+    }
+}
+
+fn handle_battle_events(mut log: ResMut<BattleLog>, mut battle_event_er: EventReader<BattleEvent>) {
+    for event in battle_event_er.read() {
+        if let Some(entry) = log.event_entry(event) {
+            log.entries.push(entry);
+        }
+    }
+}
+
 fn handle_log_message_events(
-    mut commands: Commands,
+    mut log: ResMut<BattleLog>,
     mut log_message_event_er: EventReader<LogMessageEvent>,
+) {
+    for event in log_message_event_er.read() {
+        log.entries.push(LogEntry::new(None).text(event.0.clone()));
+    }
+}
+
+fn log_button_system(
+    interaction_q: Query<(&Interaction, &LogButton), Changed<Interaction>>,
+    mut settings: ResMut<LogSettings>,
+) {
+    for (interaction, button) in interaction_q.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match *button {
+            LogButton::Collapse => settings.collapsed = !settings.collapsed,
+            LogButton::Filter(category) => settings.toggle(category),
+        }
+    }
+}
+
+fn refresh_log(
+    mut commands: Commands,
+    log: Res<BattleLog>,
+    mut settings: ResMut<LogSettings>,
     game_fonts: Res<GameFonts>,
-    log_root_q: Query<Entity, With<LogRoot>>,
+    list_q: Query<(Entity, Ref<LogList>)>,
+    mut view_q: Query<&mut Style, With<LogView>>,
+    buttons_q: Query<(&LogButton, &Children)>,
+    mut text_q: Query<&mut Text>,
 ) {
-    let Ok(log_root_e) = log_root_q.get_single() else {
+    let Ok((list, list_ref)) = list_q.get_single() else {
         return;
     };
+    if !log.is_changed() && !settings.is_changed() && !list_ref.is_added() {
+        return;
+    }
+    if log.is_changed() {
+        settings.scroll = 0.;
+    }
 
-    commands.entity(log_root_e).with_children(|parent| {
-        for event in log_message_event_er.read() {
+    if let Ok(mut style) = view_q.get_single_mut() {
+        style.display = if settings.collapsed {
+            Display::None
+        } else {
+            Display::Flex
+        };
+    }
+    for (button, children) in buttons_q.iter() {
+        let LogButton::Filter(category) = *button else {
+            continue;
+        };
+        let color = if settings.hidden.contains(&category) {
+            HIDDEN_COLOR
+        } else {
+            FONT_COLOR
+        };
+        for &child in children.iter() {
+            if let Ok(mut text) = text_q.get_mut(child) {
+                text.sections
+                    .iter_mut()
+                    .for_each(|section| section.style.color = color);
+            }
+        }
+    }
+
+    commands.entity(list).despawn_descendants();
+    commands.entity(list).with_children(|parent| {
+        for entry in log.entries.iter().filter(|entry| settings.shows(entry)) {
+            let sections = entry.sections.iter().map(|(text, color)| {
+                TextSection::new(
+                    text.clone(),
+                    TextStyle {
+                        color: *color,
+                        font_size: FONT_SIZE,
+                        font: game_fonts.font.clone(),
+                    },
+                )
+            });
             parent.spawn((
                 LogMessage,
                 TextBundle {
-                    text: Text::from_section(
-                        event.0.clone(),
-                        TextStyle {
-                            color: FONT_COLOR,
-                            font_size: FONT_SIZE,
-                            font: game_fonts.font.clone(),
-                            ..default()
-                        },
-                    ),
+                    text: Text::from_sections(sections),
                     ..default()
                 },
             ));
@@ -63,47 +429,128 @@ fn handle_log_message_events(
     });
 }
 
-fn cleanup_logs(mut commands: Commands, log_messages_q: Query<&Children, With<LogRoot>>) {
-    let Ok(log_children) = log_messages_q.get_single() else {
+fn scroll_log(
+    mut mouse_wheel_er: EventReader<MouseWheel>,
+    mut settings: ResMut<LogSettings>,
+    view_q: Query<(&RelativeCursorPosition, &Node), With<LogView>>,
+    mut list_q: Query<(&mut Style, &Node), With<LogList>>,
+) {
+    let (Ok((cursor, view)), Ok((mut style, list))) =
+        (view_q.get_single(), list_q.get_single_mut())
+    else {
         return;
     };
+    let max_scroll = (list.size().y - view.size().y).max(0.);
+    for event in mouse_wheel_er.read() {
+        if !cursor.mouse_over() {
+            continue;
+        }
+        let dy = match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_SPEED,
+            MouseScrollUnit::Pixel => event.y,
+        };
+        settings.scroll = (settings.scroll + dy).clamp(0., max_scroll);
+    }
+    style.bottom = Val::Px(-settings.scroll);
+}
 
-    log_children
-        .iter()
-        .rev()
-        .enumerate()
-        .filter(|(i, _)| *i >= MAX_MESSAGES)
-        .for_each(|(_, e)| {
-            commands.get_entity(*e).map(|e| e.despawn_recursive());
-        })
+fn spawn_log_button(parent: &mut ChildBuilder, button: LogButton, label: &str, font: &GameFonts) {
+    parent
+        .spawn((
+            button,
+            ButtonBundle {
+                background_color: BackgroundColor(Color::NONE),
+                style: Style {
+                    padding: UiRect::horizontal(Val::Px(1.)),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    color: FONT_COLOR,
+                    font_size: FONT_SIZE,
+                    font: font.font.clone(),
+                },
+            ));
+        });
 }
 
-fn setup_log(mut commands: Commands) {
-    commands.spawn((
-        LogRoot,
-        NodeBundle {
-            z_index: ZIndex::Global(i32::MAX),
-            background_color: BackgroundColor(Color::BLACK.with_a(0.5)),
-            style: Style {
-                position_type: PositionType::Absolute,
-                flex_direction: FlexDirection::ColumnReverse,
-                overflow: Overflow::clip_x(),
-                width: Val::Px(100.),
-                height: Val::Percent(100.),
-                top: Val::Percent(50.),
-                padding: UiRect::all(Val::Px(4.)),
-                justify_content: JustifyContent::Start,
+fn setup_log(mut commands: Commands, game_fonts: Res<GameFonts>) {
+    commands
+        .spawn((
+            LogRoot,
+            NodeBundle {
+                z_index: ZIndex::Global(i32::MAX),
+                background_color: BackgroundColor(Color::BLACK.with_a(0.5)),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    width: Val::Px(LOG_WIDTH),
+                    top: Val::Percent(50.),
+                    left: Val::Px(2.),
+                    padding: UiRect::all(Val::Px(2.)),
+                    row_gap: Val::Px(2.),
+                    ..default()
+                },
                 ..default()
             },
-            ..default()
-        },
-    ));
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        justify_content: JustifyContent::SpaceBetween,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_log_button(parent, LogButton::Collapse, "Log", &game_fonts);
+                    for category in LogCategory::ALL {
+                        let button = LogButton::Filter(category);
+                        spawn_log_button(parent, button, category.name(), &game_fonts);
+                    }
+                });
+            parent
+                .spawn((
+                    LogView,
+                    RelativeCursorPosition::default(),
+                    NodeBundle {
+                        style: Style {
+                            overflow: Overflow::clip(),
+                            height: Val::Px(LOG_HEIGHT),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        LogList,
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                flex_direction: FlexDirection::Column,
+                                width: Val::Percent(100.),
+                                bottom: Val::Px(0.),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ));
+                });
+        });
 }
 
 fn destroy_log(mut commands: Commands, log_root_q: Query<Entity, With<LogRoot>>) {
     for log_root_e in log_root_q.iter() {
-        commands
-            .get_entity(log_root_e)
-            .map(|e| e.despawn_recursive());
+        if let Some(e) = commands.get_entity(log_root_e) {
+            e.despawn_recursive();
+        }
     }
 }
@@ -2,16 +2,21 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-use crate::{assets::GameFonts, battle::BattleEvent};
+use crate::{
+    assets::GameFonts,
+    battle::{sim::Side, ActiveBattle, BattleEvent},
+    scene::enemy_position_x,
+};
 
 const NUMOID_DECAY_TIME: f32 = 1.;
 const NUMOID_FONT_SIZE: f32 = 10.;
 
 const NUMOID_POS_COLOR: Color = Color::SEA_GREEN;
 const NUMOID_NEG_COLOR: Color = Color::RED;
+const NUMOID_CALLOUT_COLOR: Color = Color::GRAY;
 
 const NUMOID_PLAYER_POS: Vec2 = Vec2::new(-85., -40.);
-const NUMOID_ENEMY_POS: Vec2 = Vec2::new(85., -40.);
+const NUMOID_ENEMY_OFFSET: Vec2 = Vec2::new(-15., -40.);
 
 pub struct NumoidPlugin;
 
@@ -41,6 +46,23 @@ impl Numoid {
         } else {
             NUMOID_NEG_COLOR
         };
+        let text = if number == 0 {
+            format!("-{}", number)
+        } else if number > 0 {
+            format!("+{}", number)
+        } else {
+            format!("{}", number)
+        };
+        Self::spawn_text(text, color, position, game_fonts, commands);
+    }
+
+    pub fn spawn_text(
+        text: String,
+        color: Color,
+        position: Vec2,
+        game_fonts: &GameFonts,
+        commands: &mut Commands,
+    ) {
         commands.spawn(NumoidBundle {
             numoid: Numoid {
                 color,
@@ -48,13 +70,7 @@ impl Numoid {
             },
             text_bundle: Text2dBundle {
                 text: Text::from_section(
-                    if number == 0 {
-                        format!("-{}", number)
-                    } else if number > 0 {
-                        format!("+{}", number)
-                    } else {
-                        format!("{}", number)
-                    },
+                    text,
                     TextStyle {
                         font: game_fonts.font.clone(),
                         font_size: NUMOID_FONT_SIZE,
@@ -72,18 +88,48 @@ fn spawn_numoids(
     mut commands: Commands,
     mut battle_event_er: EventReader<BattleEvent>,
     game_fonts: Res<GameFonts>,
+    battle: Option<Res<ActiveBattle>>,
 ) {
+    let enemy_count = battle.map_or(1, |battle| battle.sim.enemies.len());
+    let enemy_pos =
+        |index: usize| NUMOID_ENEMY_OFFSET + Vec2::new(enemy_position_x(index, enemy_count), 0.);
+    let side_pos = |side: &Side| match side {
+        Side::Player => NUMOID_PLAYER_POS,
+        Side::Enemy(index) => enemy_pos(*index),
+    };
     for event in battle_event_er.read() {
+        let callout = match event {
+            BattleEvent::Click(side) => Some(("click", side)),
+            BattleEvent::Misfire(side) => Some(("misfire", side)),
+            BattleEvent::Reload(side) => Some(("reload", side)),
+            BattleEvent::Stunned(side) => Some(("stunned", side)),
+            _ => None,
+        };
+        if let Some((text, side)) = callout {
+            let position = side_pos(side);
+            let text = text.to_string();
+            Numoid::spawn_text(
+                text,
+                NUMOID_CALLOUT_COLOR,
+                position,
+                &game_fonts,
+                &mut commands,
+            );
+            continue;
+        }
         match event {
             BattleEvent::PlayerHurt(amount) => {
                 Numoid::spawn(-*amount, NUMOID_PLAYER_POS, &game_fonts, &mut commands)
             }
-            BattleEvent::EnemyHurt(amount) => {
-                Numoid::spawn(-*amount, NUMOID_ENEMY_POS, &game_fonts, &mut commands)
+            BattleEvent::EnemyHurt(index, amount) => {
+                Numoid::spawn(-*amount, enemy_pos(*index), &game_fonts, &mut commands)
             }
             BattleEvent::PlayerHeal(amount) => {
                 Numoid::spawn(*amount, NUMOID_PLAYER_POS, &game_fonts, &mut commands)
             }
+            BattleEvent::EnemyHeal(index, amount) => {
+                Numoid::spawn(*amount, enemy_pos(*index), &game_fonts, &mut commands)
+            }
             _ => (),
         }
     }
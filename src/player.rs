@@ -2,13 +2,13 @@ use bevy::prelude::*;
 
 use crate::{
     assets::{GameFonts, GameSprites},
-    battle::BattleState,
     common::Hp,
+    status::{StatusBarUI, StatusEffects},
     ui::{BottomLeftUI, HealthBarUI, HealthBarUIText, FONT_COLOR, FONT_SIZE},
     AppState, BattleWins,
 };
 
-const STARTING_PLAYER_HP: i32 = 10;
+pub const STARTING_PLAYER_HP: i32 = 10;
 
 pub struct PlayerPlugin;
 
@@ -28,20 +28,18 @@ impl Plugin for PlayerPlugin {
             ),
         )
         .add_systems(OnEnter(AppState::Battling), reset_player_stats)
-        .add_systems(OnExit(AppState::Battling), reset_player_stats)
-        .add_systems(
-            OnEnter(BattleState::PlayerTurn),
-            update_player_stats.run_if(in_state(AppState::Battling)),
-        );
+        .add_systems(OnExit(AppState::Battling), reset_player_stats);
     }
 }
 
 #[derive(Component, Default, Clone, Copy)]
 pub struct Player;
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Debug)]
 pub struct PlayerStats {
     pub sea_legs: i32,
+    /// Taken off every attack, unlike sea legs it never wears off.
+    pub armor: i32,
 }
 
 #[derive(Component)]
@@ -55,6 +53,7 @@ struct PlayerBundle {
     player: Player,
     hp: Hp,
     player_stats: PlayerStats,
+    statuses: StatusEffects,
 }
 
 impl Default for PlayerBundle {
@@ -63,6 +62,7 @@ impl Default for PlayerBundle {
             player: Player,
             hp: Hp::new(STARTING_PLAYER_HP),
             player_stats: PlayerStats::default(),
+            statuses: StatusEffects::default(),
         }
     }
 }
@@ -71,23 +71,18 @@ fn setup_player(mut commands: Commands) {
     commands.spawn(PlayerBundle::default());
 }
 
-fn update_player_stats(mut player_stats_q: Query<&mut PlayerStats>) {
-    let mut player_stats = player_stats_q.single_mut();
-    player_stats.sea_legs = (player_stats.sea_legs - 1).max(0);
-}
-
 fn spawn_player_stats_ui(
     mut commands: Commands,
     game_sprites: Res<GameSprites>,
     game_fonts: Res<GameFonts>,
     player_stats_ui_q: Query<Entity, With<BottomLeftUI>>,
-    player_stats_q: Query<&PlayerStats>,
+    player_stats_q: Query<(Entity, &PlayerStats)>,
     player_hp_q: Query<&Hp, With<Player>>,
 ) {
-    let player_stats = player_stats_q.single();
+    let (player, player_stats) = player_stats_q.single();
     commands
         .entity(player_stats_ui_q.single())
-        .with_children(|mut parent| {
+        .with_children(|parent| {
             parent.spawn((
                 Player,
                 SeaLegsUI,
@@ -113,11 +108,12 @@ fn spawn_player_stats_ui(
                     ..default()
                 },
             ));
+            StatusBarUI::spawn(parent, player, Player);
             HealthBarUI::spawn(
-                &mut parent,
+                parent,
                 &game_sprites,
                 &game_fonts,
-                &player_hp_q.single(),
+                player_hp_q.single(),
                 Player,
             );
         });
@@ -169,8 +165,10 @@ fn update_battle_wins_ui(
     ui_text.sections.get_mut(1).unwrap().value = format!("{}", battle_wins.0);
 }
 
-fn reset_player_stats(mut player_stats_q: Query<&mut PlayerStats>) {
-    *player_stats_q.single_mut() = PlayerStats::default();
+fn reset_player_stats(mut player_stats_q: Query<(&mut PlayerStats, &mut StatusEffects)>) {
+    let (mut player_stats, mut statuses) = player_stats_q.single_mut();
+    *player_stats = PlayerStats::default();
+    *statuses = StatusEffects::default();
 }
 
 fn update_player_hp_ui(
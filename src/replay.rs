@@ -0,0 +1,403 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    battle::{CycleTargetInput, PlayerTurnSet, UseItemInput},
+    common::arg_value,
+    inventory::{spawn_loot, InventoryScrollUI, LootScrollUI},
+    items::ItemType,
+    rng::{start_run, RequestedSeed, Rng},
+    save::{ContinueRun, SaveGame},
+    AppState,
+};
+
+const REPLAY_DIR: &str = "replays";
+const REPLAY_STEP_TIME: f32 = 0.6;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = arg_value("--replay") {
+            match RunRecording::load(&path) {
+                Ok(recording) => {
+                    app.insert_resource(Replay::new(recording));
+                }
+                Err(err) => error!("Could not load replay {path}: {err}"),
+            }
+        }
+
+        app.init_resource::<RunRecorder>()
+            .add_event::<RecordRunEvent>()
+            .add_systems(
+                OnEnter(AppState::GameStart),
+                (
+                    start_replay.before(start_run),
+                    start_recording.after(start_run),
+                ),
+            )
+            .add_systems(
+                OnEnter(AppState::OrganizeInventory),
+                record_loot.after(spawn_loot),
+            )
+            .add_systems(OnExit(AppState::OrganizeInventory), record_arrangement)
+            .add_systems(
+                Update,
+                (record_battle_input, write_recording)
+                    .chain()
+                    .run_if(not(in_state(AppState::LoadingAssets))),
+            )
+            .add_systems(
+                Update,
+                (
+                    replay_start_game.run_if(in_state(AppState::GameStart)),
+                    replay_arrangement.run_if(in_state(AppState::OrganizeInventory)),
+                    replay_battle_input.in_set(PlayerTurnSet),
+                )
+                    .run_if(resource_exists::<Replay>),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RunEvent {
+    Continue(Box<SaveGame>),
+    Loot(Vec<ItemType>),
+    Arrangement(Vec<ItemType>),
+    UseItem,
+    CycleTarget,
+}
+
+/// Everything needed to play a run back: its seed and every choice made.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RunRecording {
+    pub seed: u64,
+    pub events: Vec<RunEvent>,
+}
+
+impl RunRecording {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::from_str(&text).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Event)]
+pub struct RecordRunEvent(pub RunEvent);
+
+/// Records the current run, rewriting its file after every event so that a
+/// crash still leaves a usable replay behind.
+#[derive(Resource, Default)]
+pub struct RunRecorder {
+    path: Option<PathBuf>,
+    recording: RunRecording,
+}
+
+impl RunRecorder {
+    pub fn recording(&self) -> &RunRecording {
+        &self.recording
+    }
+}
+
+#[derive(Resource)]
+pub struct Replay {
+    recording: RunRecording,
+    cursor: usize,
+    timer: Timer,
+}
+
+impl Replay {
+    pub fn new(recording: RunRecording) -> Self {
+        Self {
+            recording,
+            cursor: 0,
+            timer: Timer::new(
+                Duration::from_secs_f32(REPLAY_STEP_TIME),
+                TimerMode::Repeating,
+            ),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.recording.events.len()
+    }
+
+    fn peek(&self) -> Option<&RunEvent> {
+        self.recording.events.get(self.cursor)
+    }
+
+    fn advance(&mut self) {
+        self.cursor += 1;
+        self.timer.reset();
+    }
+}
+
+fn start_replay(
+    mut commands: Commands,
+    mut requested_seed: ResMut<RequestedSeed>,
+    replay: Option<Res<Replay>>,
+) {
+    let Some(replay) = replay else {
+        return;
+    };
+    if replay.is_finished() {
+        commands.remove_resource::<Replay>();
+    } else {
+        requested_seed.0 = Some(replay.recording.seed);
+    }
+}
+
+fn start_recording(mut recorder: ResMut<RunRecorder>, rng: Res<Rng>, replay: Option<Res<Replay>>) {
+    if replay.is_some_and(|replay| !replay.is_finished()) {
+        *recorder = RunRecorder::default();
+        return;
+    }
+
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    *recorder = RunRecorder {
+        path: Some(Path::new(REPLAY_DIR).join(format!("run_{started}.ron"))),
+        recording: RunRecording {
+            seed: rng.seed(),
+            events: Vec::new(),
+        },
+    };
+}
+
+fn scroll_item_types(
+    children: Option<&Children>,
+    item_types_q: &Query<&ItemType>,
+) -> Vec<ItemType> {
+    children
+        .into_iter()
+        .flat_map(|children| children.iter())
+        .filter_map(|&child| item_types_q.get(child).ok())
+        .cloned()
+        .collect()
+}
+
+fn record_loot(
+    mut record_run_ew: EventWriter<RecordRunEvent>,
+    mut replay: Option<ResMut<Replay>>,
+    loot_scroll_q: Query<Option<&Children>, With<LootScrollUI>>,
+    item_types_q: Query<&ItemType>,
+) {
+    let Ok(children) = loot_scroll_q.get_single() else {
+        return;
+    };
+    let loot = scroll_item_types(children, &item_types_q);
+
+    if let Some(replay) = replay.as_mut() {
+        if let Some(RunEvent::Loot(recorded)) = replay.peek() {
+            if *recorded != loot {
+                warn!("Replay diverged: recorded loot {recorded:?}, got {loot:?}");
+            }
+            replay.advance();
+        }
+    }
+    record_run_ew.send(RecordRunEvent(RunEvent::Loot(loot)));
+}
+
+fn record_arrangement(
+    mut record_run_ew: EventWriter<RecordRunEvent>,
+    inventory_scroll_q: Query<Option<&Children>, With<InventoryScrollUI>>,
+    item_types_q: Query<&ItemType>,
+) {
+    let Ok(children) = inventory_scroll_q.get_single() else {
+        return;
+    };
+    let arrangement = scroll_item_types(children, &item_types_q);
+    record_run_ew.send(RecordRunEvent(RunEvent::Arrangement(arrangement)));
+}
+
+fn record_battle_input(
+    mut use_item_input_er: EventReader<UseItemInput>,
+    mut cycle_target_input_er: EventReader<CycleTargetInput>,
+    mut record_run_ew: EventWriter<RecordRunEvent>,
+) {
+    for _ in cycle_target_input_er.read() {
+        record_run_ew.send(RecordRunEvent(RunEvent::CycleTarget));
+    }
+    for _ in use_item_input_er.read() {
+        record_run_ew.send(RecordRunEvent(RunEvent::UseItem));
+    }
+}
+
+fn write_recording(
+    mut record_run_er: EventReader<RecordRunEvent>,
+    mut recorder: ResMut<RunRecorder>,
+) {
+    let Some(path) = recorder.path.clone() else {
+        record_run_er.clear();
+        return;
+    };
+    if record_run_er.is_empty() {
+        return;
+    }
+    for RecordRunEvent(event) in record_run_er.read() {
+        recorder.recording.events.push(event.clone());
+    }
+
+    let saved = fs::create_dir_all(REPLAY_DIR)
+        .map_err(|e| e.to_string())
+        .and_then(|_| recorder.recording.save(&path));
+    if let Err(err) = saved {
+        warn!("Could not write run recording {}: {err}", path.display());
+    }
+}
+
+fn replay_start_game(
+    mut commands: Commands,
+    mut replay: ResMut<Replay>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    time: Res<Time>,
+) {
+    if replay.is_finished() {
+        return;
+    }
+    if replay.timer.tick(time.delta()).just_finished() {
+        if let Some(RunEvent::Continue(save)) = replay.peek().cloned() {
+            commands.insert_resource(ContinueRun(*save));
+            replay.advance();
+        } else {
+            replay.timer.reset();
+        }
+        next_app_state.set(AppState::OrganizeInventory);
+    }
+}
+
+fn replay_arrangement(
+    mut commands: Commands,
+    mut replay: ResMut<Replay>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    inventory_scroll_q: Query<(Entity, Option<&Children>), With<InventoryScrollUI>>,
+    loot_scroll_q: Query<(Entity, Option<&Children>), With<LootScrollUI>>,
+    item_types_q: Query<&ItemType>,
+    time: Res<Time>,
+) {
+    let Some(RunEvent::Arrangement(arrangement)) = replay.peek().cloned() else {
+        return;
+    };
+    if !replay.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let (Ok((inventory_scroll, inventory_items)), Ok((loot_scroll, loot_items))) =
+        (inventory_scroll_q.get_single(), loot_scroll_q.get_single())
+    else {
+        return;
+    };
+
+    let mut available: Vec<Entity> = inventory_items
+        .into_iter()
+        .chain(loot_items)
+        .flat_map(|children| children.iter())
+        .copied()
+        .collect();
+    let mut arranged = Vec::new();
+    for item_type in arrangement.iter() {
+        match available
+            .iter()
+            .position(|&item| item_types_q.get(item).is_ok_and(|t| t == item_type))
+        {
+            Some(i) => arranged.push(available.remove(i)),
+            None => warn!("Replay diverged: no {item_type} to arrange"),
+        }
+    }
+
+    commands.entity(loot_scroll).push_children(&available);
+    commands
+        .entity(inventory_scroll)
+        .replace_children(&arranged);
+    next_app_state.set(AppState::Battling);
+    replay.advance();
+}
+
+fn replay_battle_input(
+    mut replay: ResMut<Replay>,
+    mut use_item_input_ew: EventWriter<UseItemInput>,
+    mut cycle_target_input_ew: EventWriter<CycleTargetInput>,
+    time: Res<Time>,
+) {
+    if !matches!(
+        replay.peek(),
+        Some(RunEvent::UseItem | RunEvent::CycleTarget)
+    ) {
+        return;
+    }
+    if replay.timer.tick(time.delta()).just_finished() {
+        match replay.peek() {
+            Some(RunEvent::CycleTarget) => {
+                cycle_target_input_ew.send(CycleTargetInput);
+            }
+            _ => {
+                use_item_input_ew.send(UseItemInput);
+            }
+        }
+        replay.advance();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{common::Hp, inventory::LootBonus};
+
+    use super::*;
+
+    fn recording() -> RunRecording {
+        let save = SaveGame {
+            battle_wins: 2,
+            hp: Hp::new(30),
+            items: vec![],
+            rng: Rng::from_seed(3),
+            loot_bonus: LootBonus::default(),
+        };
+        RunRecording {
+            seed: 3,
+            events: vec![
+                RunEvent::Continue(Box::new(save)),
+                RunEvent::Loot(vec![ItemType::new("Grog")]),
+                RunEvent::Arrangement(vec![ItemType::new("Grog")]),
+                RunEvent::UseItem,
+                RunEvent::CycleTarget,
+            ],
+        }
+    }
+
+    #[test]
+    fn recordings_round_trip_through_a_file() {
+        let recording = recording();
+        let path =
+            std::env::temp_dir().join(format!("replay_round_trip_{}.ron", std::process::id()));
+        recording.save(&path).unwrap();
+        let loaded = RunRecording::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, Ok(recording));
+    }
+
+    #[test]
+    fn replays_step_through_the_recorded_events() {
+        let recording = recording();
+        let mut replay = Replay::new(recording.clone());
+        for event in recording.events.iter() {
+            assert!(!replay.is_finished());
+            assert_eq!(replay.peek(), Some(event));
+            replay.advance();
+        }
+        assert!(replay.is_finished());
+        assert_eq!(replay.peek(), None);
+        assert!(Replay::new(RunRecording::default()).is_finished());
+    }
+}
@@ -1,17 +1,100 @@
 use bevy::prelude::*;
-use rand::rngs::ThreadRng;
+use rand::{Rng as _, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{common::arg_value, AppState};
+
+const MAX_RANDOM_SEED: u64 = 1_000_000_000;
 
 pub struct RngPlugin;
 
 impl Plugin for RngPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, init_rng);
+        app.insert_resource(RequestedSeed(seed_from_args()))
+            .insert_resource(Rng::from_seed(random_seed()))
+            .add_systems(OnEnter(AppState::GameStart), start_run);
+    }
+}
+
+/// Seed passed on the command line with `--seed <n>`, used for the next run.
+#[derive(Resource, Default)]
+pub struct RequestedSeed(pub Option<u64>);
+
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rng {
+    seed: u64,
+    rng: ChaCha12Rng,
+}
+
+impl Rng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
     }
 }
 
-#[derive(Clone)]
-pub struct Rng(pub ThreadRng);
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen_range(0..MAX_RANDOM_SEED)
+}
+
+fn seed_from_args() -> Option<u64> {
+    arg_value("--seed").and_then(|seed| seed.parse().ok())
+}
 
-fn init_rng(world: &mut World) {
-    world.insert_non_send_resource(Rng(rand::thread_rng()));
+pub fn start_run(mut rng: ResMut<Rng>, mut requested_seed: ResMut<RequestedSeed>) {
+    let seed = requested_seed.0.take().unwrap_or_else(random_seed);
+    *rng = Rng::from_seed(seed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draws(rng: &mut Rng) -> Vec<u64> {
+        (0..8).map(|_| rng.next_u64()).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_sequence() {
+        let first = draws(&mut Rng::from_seed(42));
+        assert_eq!(draws(&mut Rng::from_seed(42)), first);
+        assert_ne!(draws(&mut Rng::from_seed(43)), first);
+    }
+
+    #[test]
+    fn restored_rngs_carry_on_mid_stream() {
+        let mut rng = Rng::from_seed(42);
+        draws(&mut rng);
+        rng.next_u32();
+        let text = ron::to_string(&rng).unwrap();
+        let mut restored: Rng = ron::from_str(&text).unwrap();
+        assert_eq!(restored, rng);
+        assert_eq!(restored.seed(), 42);
+        assert_eq!(draws(&mut restored), draws(&mut rng));
+    }
 }
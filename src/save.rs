@@ -0,0 +1,393 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::{GameFonts, GameSprites},
+    common::Hp,
+    inventory::{spawn_loot, InventoryScrollUI, LootBonus},
+    items::{
+        abilities::{AbilityKind, AbilityModifier},
+        definitions::ItemDefinitions,
+        ItemComponents, ItemComponentsQueryReadOnly, ItemType,
+    },
+    player::Player,
+    replay::{RecordRunEvent, Replay, RunEvent},
+    rng::Rng,
+    ui::{BottomRightUI, FONT_COLOR},
+    AppState, BattleWins,
+};
+
+const SAVE_DIR: &str = "saves";
+const SAVE_FILE: &str = "current_run.ron";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::GameStart), spawn_continue_button)
+            .add_systems(OnExit(AppState::GameStart), destroy_continue_button)
+            .add_systems(
+                Update,
+                continue_button_system.run_if(any_with_component::<ContinueButton>),
+            )
+            .add_systems(
+                OnEnter(AppState::OrganizeInventory),
+                (
+                    restore_save.run_if(resource_exists::<ContinueRun>),
+                    write_save.run_if(not(resource_exists::<Replay>)),
+                )
+                    .chain()
+                    .before(spawn_loot),
+            )
+            .add_systems(
+                OnEnter(AppState::GameOver),
+                delete_save.run_if(not(resource_exists::<Replay>)),
+            );
+    }
+}
+
+/// The parts of an item that change during a run; everything else comes
+/// from its `ItemType`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SavedItem {
+    pub item_type: ItemType,
+    pub consumable: Option<i32>,
+    pub loaded: Option<usize>,
+    #[serde(default)]
+    pub modifiers: Vec<(AbilityKind, AbilityModifier)>,
+}
+
+impl From<&ItemComponents> for SavedItem {
+    fn from(item: &ItemComponents) -> Self {
+        Self {
+            item_type: item.item_type.clone(),
+            consumable: item.consumable.as_ref().map(|c| c.0),
+            loaded: item.flintlock.as_ref().map(|f| f.loaded()),
+            modifiers: AbilityKind::ALL
+                .into_iter()
+                .filter_map(|kind| item.ability(kind).map(|a| (kind, *a.modifier())))
+                .filter(|(_, modifier)| *modifier != AbilityModifier::NONE)
+                .collect(),
+        }
+    }
+}
+
+impl SavedItem {
+    pub fn components(&self, item_definitions: &ItemDefinitions) -> Option<ItemComponents> {
+        let mut item = item_definitions.components(&self.item_type)?;
+        if let (Some(consumable), Some(uses)) = (item.consumable.as_mut(), self.consumable) {
+            consumable.0 = uses;
+        }
+        if let (Some(flintlock), Some(loaded)) = (item.flintlock.as_mut(), self.loaded) {
+            flintlock.load(loaded);
+        }
+        for &(kind, modifier) in self.modifiers.iter() {
+            if let Some(ability) = item.ability_mut(kind) {
+                *ability.modifier_mut() = modifier;
+            }
+        }
+        Some(item)
+    }
+}
+
+/// A run as it stood when the player last entered the inventory, before the
+/// loot for that visit was rolled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SaveGame {
+    pub battle_wins: usize,
+    pub hp: Hp,
+    pub items: Vec<SavedItem>,
+    pub rng: Rng,
+    /// Owed for the battle just won, rolled into the loot after saving.
+    #[serde(default)]
+    pub loot_bonus: LootBonus,
+}
+
+impl SaveGame {
+    pub fn path() -> std::path::PathBuf {
+        Path::new(SAVE_DIR).join(SAVE_FILE)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::from_str(&text).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| e.to_string())
+    }
+}
+
+/// Save chosen with the "Continue" button, restored on the next
+/// `OrganizeInventory` instead of starting fresh.
+#[derive(Resource)]
+pub struct ContinueRun(pub SaveGame);
+
+#[derive(Component)]
+struct ContinueButton;
+
+fn spawn_continue_button(
+    mut commands: Commands,
+    game_sprites: Res<GameSprites>,
+    game_fonts: Res<GameFonts>,
+    bottom_right_ui_q: Query<Entity, With<BottomRightUI>>,
+) {
+    if !SaveGame::path().exists() {
+        return;
+    }
+
+    let continue_button = commands
+        .spawn((
+            ContinueButton,
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(65.),
+                    height: Val::Px(16.),
+                    padding: UiRect {
+                        left: Val::Px(22.),
+                        top: Val::Px(6.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                image: game_sprites.start_game_button.clone().into(),
+                ..default()
+            },
+        ))
+        .id();
+
+    let button_text = commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "Continue",
+                TextStyle {
+                    color: FONT_COLOR,
+                    font_size: 7.,
+                    font: game_fonts.font.clone(),
+                },
+            ),
+            ..default()
+        })
+        .id();
+
+    commands.entity(continue_button).add_child(button_text);
+    commands
+        .entity(bottom_right_ui_q.single())
+        .add_child(continue_button);
+}
+
+fn destroy_continue_button(mut commands: Commands, buttons_q: Query<Entity, With<ContinueButton>>) {
+    for button in buttons_q.iter() {
+        commands.entity(button).despawn_recursive();
+    }
+}
+
+fn continue_button_system(
+    mut commands: Commands,
+    mut interaction_q: Query<(&Interaction, &mut UiImage), With<ContinueButton>>,
+    mut app_state: ResMut<NextState<AppState>>,
+    game_sprites: Res<GameSprites>,
+) {
+    let (interaction, mut image) = interaction_q.single_mut();
+    match *interaction {
+        Interaction::Pressed => match SaveGame::load(SaveGame::path()) {
+            Ok(save) => {
+                commands.insert_resource(ContinueRun(save));
+                app_state.set(AppState::OrganizeInventory);
+            }
+            Err(err) => warn!("Could not load save: {err}"),
+        },
+        Interaction::Hovered => image.texture = game_sprites.start_game_button_hover.clone(),
+        Interaction::None => image.texture = game_sprites.start_game_button.clone(),
+    };
+}
+
+fn restore_save(
+    mut commands: Commands,
+    mut battle_wins: ResMut<BattleWins>,
+    mut rng: ResMut<Rng>,
+    mut loot_bonus: ResMut<LootBonus>,
+    mut record_run_ew: EventWriter<RecordRunEvent>,
+    mut player_hp_q: Query<&mut Hp, With<Player>>,
+    continue_run: Res<ContinueRun>,
+    game_sprites: Res<GameSprites>,
+    item_definitions: Res<ItemDefinitions>,
+    inventory_scroll_q: Query<Entity, With<InventoryScrollUI>>,
+) {
+    let save = &continue_run.0;
+    battle_wins.0 = save.battle_wins;
+    *rng = save.rng.clone();
+    *loot_bonus = save.loot_bonus;
+    if let Ok(mut hp) = player_hp_q.get_single_mut() {
+        *hp = save.hp.clone();
+    }
+    commands
+        .entity(inventory_scroll_q.single())
+        .despawn_descendants()
+        .with_children(|parent| {
+            for item in save.items.iter() {
+                match item.components(&item_definitions) {
+                    Some(components) => {
+                        item_definitions.spawn_components(components, parent, &game_sprites)
+                    }
+                    None => warn!("Dropping unknown item {} from save", item.item_type),
+                }
+            }
+        });
+
+    record_run_ew.send(RecordRunEvent(RunEvent::Continue(Box::new(save.clone()))));
+    commands.remove_resource::<ContinueRun>();
+}
+
+fn write_save(
+    battle_wins: Res<BattleWins>,
+    rng: Res<Rng>,
+    loot_bonus: Res<LootBonus>,
+    player_hp_q: Query<&Hp, With<Player>>,
+    inventory_scroll_q: Query<&Children, With<InventoryScrollUI>>,
+    items_q: Query<ItemComponentsQueryReadOnly>,
+) {
+    let Ok(hp) = player_hp_q.get_single() else {
+        return;
+    };
+    let items = inventory_scroll_q
+        .get_single()
+        .map(|children| {
+            children
+                .iter()
+                .filter_map(|&child| items_q.get(child).ok())
+                .map(|item| SavedItem::from(&item.components()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let save = SaveGame {
+        battle_wins: battle_wins.0,
+        hp: hp.clone(),
+        items,
+        rng: rng.clone(),
+        loot_bonus: *loot_bonus,
+    };
+
+    let saved = fs::create_dir_all(SAVE_DIR)
+        .map_err(|e| e.to_string())
+        .and_then(|_| save.save(SaveGame::path()));
+    if let Err(err) = saved {
+        warn!("Could not write save: {err}");
+    }
+}
+
+fn delete_save() {
+    let path = SaveGame::path();
+    if path.exists() {
+        if let Err(err) = fs::remove_file(&path) {
+            warn!("Could not delete save {}: {err}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::items::Rarity;
+
+    use super::*;
+
+    fn item_definitions() -> ItemDefinitions {
+        ron::from_str(
+            r#"(
+                starting_loot: [],
+                items: [(
+                    id: "PowderFlask",
+                    name: "Powder Flask",
+                    icon: 0,
+                    abilities: [Damage(3), SeaLegs(2)],
+                    attributes: [Flintlock(ammo: Shot, capacity: 3)],
+                    consumable: Some(3),
+                )],
+            )"#,
+        )
+        .unwrap()
+    }
+
+    fn worn_flask(item_definitions: &ItemDefinitions) -> ItemComponents {
+        let mut flask = item_definitions
+            .components(&ItemType::new("PowderFlask"))
+            .unwrap();
+        flask.consumable.as_mut().unwrap().0 = 1;
+        flask.flintlock.as_mut().unwrap().load(2);
+        *flask
+            .ability_mut(AbilityKind::Damage)
+            .unwrap()
+            .modifier_mut() = AbilityModifier {
+            turn: 0,
+            battle: 2,
+            run: 4,
+        };
+        *flask
+            .ability_mut(AbilityKind::SeaLegs)
+            .unwrap()
+            .modifier_mut() = AbilityModifier {
+            turn: 1,
+            battle: 0,
+            run: 0,
+        };
+        flask
+    }
+
+    #[test]
+    fn worn_items_survive_a_round_trip() {
+        let item_definitions = item_definitions();
+        let flask = worn_flask(&item_definitions);
+        let saved = SavedItem::from(&flask);
+        let text = ron::to_string(&saved).unwrap();
+        let restored: SavedItem = ron::from_str(&text).unwrap();
+        assert_eq!(restored, saved);
+
+        let item = restored.components(&item_definitions).unwrap();
+        assert_eq!(item.consumable.as_ref().map(|c| c.0), Some(1));
+        assert_eq!(item.flintlock.as_ref().map(|f| f.loaded()), Some(2));
+        for kind in [AbilityKind::Damage, AbilityKind::SeaLegs] {
+            assert_eq!(
+                item.ability(kind).map(|a| *a.modifier()),
+                flask.ability(kind).map(|a| *a.modifier()),
+            );
+        }
+        assert_eq!(SavedItem::from(&item), saved);
+    }
+
+    #[test]
+    fn unknown_items_are_not_restored() {
+        let saved = SavedItem {
+            item_type: ItemType::new("GhostShip"),
+            consumable: None,
+            loaded: None,
+            modifiers: vec![],
+        };
+        assert!(saved.components(&item_definitions()).is_none());
+    }
+
+    #[test]
+    fn save_games_round_trip_through_a_file() {
+        let item_definitions = item_definitions();
+        let mut hp = Hp::new(30);
+        hp.current = 12;
+        let save = SaveGame {
+            battle_wins: 4,
+            hp,
+            items: vec![SavedItem::from(&worn_flask(&item_definitions))],
+            rng: Rng::from_seed(9),
+            loot_bonus: LootBonus {
+                guaranteed: Some(Rarity::Scarce),
+                upgrades: 1,
+            },
+        };
+        let path = std::env::temp_dir().join(format!("save_round_trip_{}.ron", std::process::id()));
+        save.save(&path).unwrap();
+        let loaded = SaveGame::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, Ok(save));
+    }
+}
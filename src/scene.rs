@@ -2,10 +2,26 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-use crate::{assets::GameSprites, battle::BattleEvent, AppState, GAME_HEIGHT};
+use crate::{
+    assets::{
+        GameFonts, GameSprites, ICON_INDEX_INTENT_ATTACK, ICON_INDEX_INTENT_BUFF,
+        ICON_INDEX_INTENT_DEBUFF, ICON_INDEX_INTENT_DEFEND, ICON_INDEX_INTENT_UNKNOWN,
+        ICON_INDEX_SCROLL_MARKER,
+    },
+    battle::{sim::Intent, ActiveBattle, BattleEvent, BattleState},
+    common::Hp,
+    enemy::{spawn_enemy, Enemy, EnemyArchetype, EnemySlot},
+    AppState, GAME_HEIGHT,
+};
 
 const FLOOR_HEIGHT: f32 = 21.;
 const PIRATE_HEIGHT: f32 = 32.;
+const ENEMY_POSITION_X: f32 = 100.;
+const ENEMY_SPACING: f32 = 36.;
+const INTENT_OFFSET: f32 = 22.;
+const INTENT_SPACING: f32 = 14.;
+/// Intents shown per enemy, enough for a swift elite's two actions.
+const INTENT_ROWS: usize = 2;
 
 pub struct ScenePlugin;
 
@@ -16,7 +32,10 @@ impl Plugin for ScenePlugin {
         })
         .add_systems(OnEnter(AppState::InitGame), setup_scene)
         .add_systems(OnEnter(AppState::GameStart), spawn_player_pirate)
-        .add_systems(OnEnter(AppState::Battling), spawn_enemy_pirate)
+        .add_systems(
+            OnEnter(AppState::Battling),
+            spawn_enemy_pirate.after(spawn_enemy),
+        )
         .add_systems(OnEnter(AppState::GameStart), despawn_enemy_pirate)
         .add_systems(OnEnter(AppState::OrganizeInventory), despawn_enemy_pirate)
         .add_systems(OnEnter(AppState::GameOver), despawn_player_pirate)
@@ -26,7 +45,17 @@ impl Plugin for ScenePlugin {
         )
         .add_systems(
             Update,
-            (handle_enemy_damanged, handle_enemy_attack).run_if(any_with_component::<EnemyPirate>),
+            (
+                handle_enemy_damanged,
+                handle_enemy_attack,
+                hide_dead_enemy_pirates,
+                update_target_marker.run_if(resource_exists::<ActiveBattle>),
+                update_enemy_intents.run_if(
+                    in_state(BattleState::PlayerTurn)
+                        .and_then(resource_exists_and_changed::<ActiveBattle>),
+                ),
+            )
+                .run_if(any_with_component::<EnemyPirate>),
         )
         .add_systems(
             Update,
@@ -45,6 +74,16 @@ struct PlayerPirate;
 #[derive(Component)]
 struct EnemyPirate;
 
+#[derive(Component)]
+struct TargetMarker;
+
+/// Shows one of the actions the enemy in `slot` will take on its next turn.
+#[derive(Component)]
+struct EnemyIntent {
+    slot: usize,
+    action: usize,
+}
+
 #[derive(Resource)]
 struct GlobalAnimationTimer {
     timer: Timer,
@@ -113,19 +152,106 @@ fn despawn_player_pirate(
     }
 }
 
-fn spawn_enemy_pirate(mut commands: Commands, game_sprites: Res<GameSprites>) {
+/// Horizontal position of the enemy at `index`, with the group centred where
+/// a lone enemy would stand.
+pub fn enemy_position_x(index: usize, count: usize) -> f32 {
+    ENEMY_POSITION_X + (index as f32 - (count.max(1) - 1) as f32 / 2.) * ENEMY_SPACING
+}
+
+fn spawn_enemy_pirate(
+    mut commands: Commands,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    asset_server: Res<AssetServer>,
+    game_sprites: Res<GameSprites>,
+    game_fonts: Res<GameFonts>,
+    enemy_q: Query<(&EnemyArchetype, &EnemySlot), With<Enemy>>,
+) {
+    let count = enemy_q.iter().count();
+    for (archetype, &slot) in enemy_q.iter() {
+        spawn_enemy_pirate_sprite(
+            &mut commands,
+            &mut texture_atlas_layouts,
+            &asset_server,
+            archetype,
+            slot,
+            count,
+        );
+        for action in 0..INTENT_ROWS {
+            let intent = EnemyIntent {
+                slot: slot.0,
+                action,
+            };
+            spawn_enemy_intent(
+                &mut commands,
+                &game_sprites,
+                &game_fonts,
+                archetype,
+                intent,
+                count,
+            );
+        }
+    }
+    if count > 1 {
+        commands.spawn((
+            EnemyPirate,
+            TargetMarker,
+            SpriteSheetBundle {
+                transform: Transform::from_translation(Vec3::new(
+                    enemy_position_x(0, count),
+                    -GAME_HEIGHT / 2. + FLOOR_HEIGHT + PIRATE_HEIGHT + 4.,
+                    1.,
+                )),
+                sprite: Sprite {
+                    flip_y: true,
+                    ..default()
+                },
+                texture: game_sprites.items_tile_sheet.clone(),
+                atlas: TextureAtlas {
+                    layout: game_sprites.items_tile_layout.clone(),
+                    index: ICON_INDEX_SCROLL_MARKER,
+                },
+                ..default()
+            },
+        ));
+    }
+}
+
+fn spawn_enemy_pirate_sprite(
+    commands: &mut Commands,
+    texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
+    asset_server: &AssetServer,
+    archetype: &EnemyArchetype,
+    slot: EnemySlot,
+    count: usize,
+) {
+    let sprite = &archetype.sprite;
+    let layout = TextureAtlasLayout::from_grid(
+        Vec2::new(sprite.tile_size.0, sprite.tile_size.1),
+        sprite.columns,
+        1,
+        None,
+        None,
+    );
     commands.spawn((
         EnemyPirate,
-        AnimationTimer { frames: 2 },
+        slot,
+        AnimationTimer {
+            frames: sprite.idle_frames,
+        },
         SpriteSheetBundle {
             transform: Transform::from_translation(Vec3::new(
-                100.,
-                -GAME_HEIGHT / 2. + FLOOR_HEIGHT + PIRATE_HEIGHT / 2.,
+                enemy_position_x(slot.0, count),
+                -GAME_HEIGHT / 2. + FLOOR_HEIGHT + PIRATE_HEIGHT * sprite.scale / 2.,
                 0.,
-            )),
-            texture: game_sprites.skeleton_sheet.clone(),
+            ))
+            .with_scale(Vec3::splat(sprite.scale)),
+            sprite: Sprite {
+                flip_x: sprite.flip_x,
+                ..default()
+            },
+            texture: asset_server.load(&sprite.path),
             atlas: TextureAtlas {
-                layout: game_sprites.skeleton_layout.clone(),
+                layout: texture_atlas_layouts.add(layout),
                 index: 0,
             },
             ..default()
@@ -133,6 +259,83 @@ fn spawn_enemy_pirate(mut commands: Commands, game_sprites: Res<GameSprites>) {
     ));
 }
 
+fn spawn_enemy_intent(
+    commands: &mut Commands,
+    game_sprites: &GameSprites,
+    game_fonts: &GameFonts,
+    archetype: &EnemyArchetype,
+    intent: EnemyIntent,
+    count: usize,
+) {
+    let position = Vec3::new(
+        enemy_position_x(intent.slot, count) - 4.,
+        -GAME_HEIGHT / 2.
+            + FLOOR_HEIGHT
+            + PIRATE_HEIGHT * archetype.sprite.scale
+            + INTENT_OFFSET
+            + intent.action as f32 * INTENT_SPACING,
+        1.,
+    );
+    commands
+        .spawn((
+            EnemyPirate,
+            intent,
+            SpriteSheetBundle {
+                transform: Transform::from_translation(position),
+                texture: game_sprites.items_tile_sheet.clone(),
+                atlas: TextureAtlas {
+                    layout: game_sprites.items_tile_layout.clone(),
+                    index: ICON_INDEX_INTENT_ATTACK,
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: game_fonts.font.clone(),
+                        font_size: 10.,
+                        color: Color::WHITE,
+                    },
+                ),
+                transform: Transform::from_translation(Vec3::new(12., 0., 0.)),
+                ..default()
+            });
+        });
+}
+
+fn update_enemy_intents(
+    battle: Res<ActiveBattle>,
+    mut intent_q: Query<(&EnemyIntent, &mut TextureAtlas, &mut Visibility, &Children)>,
+    mut text_q: Query<&mut Text>,
+) {
+    let intents: Vec<Vec<Intent>> = (0..battle.sim.enemies.len())
+        .map(|index| battle.sim.enemy_intents(index))
+        .collect();
+    for (intent, mut atlas, mut visibility, children) in intent_q.iter_mut() {
+        let Some(&next) = intents.get(intent.slot).and_then(|i| i.get(intent.action)) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let (index, amount) = match next {
+            Intent::Attack(amount) => (ICON_INDEX_INTENT_ATTACK, Some(amount)),
+            Intent::Buff(amount) => (ICON_INDEX_INTENT_BUFF, Some(amount)),
+            Intent::Defend(amount) => (ICON_INDEX_INTENT_DEFEND, Some(amount)),
+            Intent::Debuff(amount) => (ICON_INDEX_INTENT_DEBUFF, Some(amount)),
+            Intent::Unknown => (ICON_INDEX_INTENT_UNKNOWN, None),
+        };
+        atlas.index = index;
+        *visibility = Visibility::Inherited;
+        let mut texts = text_q.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = amount.map_or_else(String::new, |a| a.to_string());
+        }
+    }
+}
+
 fn despawn_enemy_pirate(mut commands: Commands, enemy_pirate_q: Query<Entity, With<EnemyPirate>>) {
     for enemy_pirate in enemy_pirate_q.iter() {
         commands.entity(enemy_pirate).despawn_recursive();
@@ -188,36 +391,72 @@ fn handle_player_damanged(
 fn handle_enemy_damanged(
     mut battle_event_er: EventReader<BattleEvent>,
     mut commands: Commands,
-    enemy_sprite_q: Query<Entity, With<EnemyPirate>>,
+    enemy_sprite_q: Query<(Entity, &EnemySlot), With<EnemyPirate>>,
 ) {
-    let enemy_sprite = enemy_sprite_q.single();
     for event in battle_event_er.read() {
-        if !matches!(event, BattleEvent::EnemyHurt(_)) {
+        let BattleEvent::EnemyHurt(index, _) = event else {
             continue;
+        };
+        for (enemy_sprite, _) in enemy_sprite_q.iter().filter(|(_, slot)| slot.0 == *index) {
+            commands.entity(enemy_sprite).insert(AnimationFlash {
+                color: Color::RED,
+                timer: Timer::new(Duration::from_secs_f32(0.3), TimerMode::Once),
+            });
         }
-        commands.entity(enemy_sprite).insert(AnimationFlash {
-            color: Color::RED,
-            timer: Timer::new(Duration::from_secs_f32(0.3), TimerMode::Once),
-        });
     }
 }
 
 fn handle_enemy_attack(
     mut commands: Commands,
     mut battle_event_er: EventReader<BattleEvent>,
-    mut enemy_sprite_q: Query<(Entity, &mut TextureAtlas), With<EnemyPirate>>,
+    mut enemy_sprite_q: Query<(Entity, &mut TextureAtlas, &EnemySlot), With<EnemyPirate>>,
+    enemy_q: Query<(&EnemyArchetype, &EnemySlot), With<Enemy>>,
 ) {
-    let (entity, mut atlas) = enemy_sprite_q.single_mut();
     for event in battle_event_er.read() {
-        if !matches!(event, BattleEvent::EnemyAttack) {
+        let BattleEvent::EnemyAttack(index) = event else {
             continue;
+        };
+        let Some(attack_frame) = enemy_q
+            .iter()
+            .find(|(_, slot)| slot.0 == *index)
+            .and_then(|(archetype, _)| archetype.sprite.attack_frame)
+        else {
+            continue;
+        };
+        for (entity, mut atlas, _) in enemy_sprite_q
+            .iter_mut()
+            .filter(|(.., slot)| slot.0 == *index)
+        {
+            atlas.index = attack_frame;
+            commands.entity(entity).insert(AnimationOverride(Timer::new(
+                Duration::from_secs_f32(0.2),
+                TimerMode::Once,
+            )));
+        }
+    }
+}
+
+fn hide_dead_enemy_pirates(
+    mut enemy_sprite_q: Query<(&mut Visibility, &EnemySlot), With<EnemyPirate>>,
+    enemy_q: Query<(&Hp, &EnemySlot), (With<Enemy>, Changed<Hp>)>,
+) {
+    for (hp, slot) in enemy_q.iter() {
+        for (mut visibility, _) in enemy_sprite_q.iter_mut().filter(|(_, s)| *s == slot) {
+            *visibility = if hp.is_dead() {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
         }
+    }
+}
 
-        atlas.index = 2;
-        commands.entity(entity).insert(AnimationOverride(Timer::new(
-            Duration::from_secs_f32(0.2),
-            TimerMode::Once,
-        )));
+fn update_target_marker(
+    mut target_marker_q: Query<&mut Transform, With<TargetMarker>>,
+    battle: Res<ActiveBattle>,
+) {
+    for mut transform in target_marker_q.iter_mut() {
+        transform.translation.x = enemy_position_x(battle.sim.target, battle.sim.enemies.len());
     }
 }
 
@@ -0,0 +1,224 @@
+use crate::{
+    battle::sim::{BattleOutcome, BattleSim, SimEnemy, SimPlayer},
+    items::ItemComponents,
+};
+
+/// Scrolls up to this size are solved by trying every ordering; larger ones
+/// fall back to a local search.
+const EXHAUSTIVE_LIMIT: usize = 7;
+const MAX_SEARCH_ROUNDS: usize = 50;
+const MAX_BATTLE_ROUNDS: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolverGoal {
+    FastestKill,
+    LeastDamage,
+}
+
+/// An ordering of the scroll and how the battle plays out with it.
+#[derive(Clone, Debug)]
+pub struct Solution {
+    pub order: Vec<usize>,
+    pub outcome: Option<BattleOutcome>,
+    pub turns: usize,
+    pub damage_taken: i32,
+    pub enemy_hp_left: i32,
+}
+
+impl Solution {
+    fn evaluate(
+        order: Vec<usize>,
+        items: &[ItemComponents],
+        player: &SimPlayer,
+        enemies: &[SimEnemy],
+    ) -> Self {
+        let mut sim = BattleSim::new(
+            order.iter().map(|&i| items[i].clone()).collect(),
+            player.clone(),
+            enemies.to_vec(),
+        );
+        let outcome = sim.run(MAX_BATTLE_ROUNDS);
+        Self {
+            order,
+            outcome,
+            turns: sim.turns,
+            damage_taken: player.hp.current - sim.player.hp.current,
+            enemy_hp_left: sim.enemy_hp_left(),
+        }
+    }
+
+    /// Higher is better. Wins always beat losses; losses are ranked by how
+    /// close the enemies came to dying.
+    fn score(&self, goal: SolverGoal) -> (bool, i32, i32, i32) {
+        let turns = self.turns as i32;
+        match (self.outcome, goal) {
+            (Some(BattleOutcome::Won), SolverGoal::FastestKill) => {
+                (true, -turns, -self.damage_taken, 0)
+            }
+            (Some(BattleOutcome::Won), SolverGoal::LeastDamage) => {
+                (true, -self.damage_taken, -turns, 0)
+            }
+            _ => (false, -self.enemy_hp_left, -self.damage_taken, -turns),
+        }
+    }
+}
+
+/// Searches orderings of `items` for the one that best meets `goal` against
+/// `enemies`. The returned order holds indices into `items`.
+pub fn solve_arrangement(
+    items: &[ItemComponents],
+    player: &SimPlayer,
+    enemies: &[SimEnemy],
+    goal: SolverGoal,
+) -> Solution {
+    let evaluate = |order: Vec<usize>| Solution::evaluate(order, items, player, enemies);
+    let mut best = evaluate((0..items.len()).collect());
+
+    if items.len() <= EXHAUSTIVE_LIMIT {
+        for order in permutations(items.len()) {
+            let solution = evaluate(order);
+            if solution.score(goal) > best.score(goal) {
+                best = solution;
+            }
+        }
+        return best;
+    }
+
+    for _ in 0..MAX_SEARCH_ROUNDS {
+        let mut improved = false;
+        for from in 0..items.len() {
+            for to in 0..items.len() {
+                if from == to {
+                    continue;
+                }
+                let mut order = best.order.clone();
+                let item = order.remove(from);
+                order.insert(to, item);
+                let solution = evaluate(order);
+                if solution.score(goal) > best.score(goal) {
+                    best = solution;
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    best
+}
+
+fn permutations(len: usize) -> Vec<Vec<usize>> {
+    if len == 0 {
+        return vec![Vec::new()];
+    }
+    permutations(len - 1)
+        .into_iter()
+        .flat_map(|order| {
+            (0..len).map(move |at| {
+                let mut order = order.clone();
+                order.insert(at, len - 1);
+                order
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::Hp,
+        enemy::EnemyBehaviour,
+        items::{
+            abilities::{AbilityTarget, Damage, SeaLegs, TargetFilter},
+            attributes::{AmmoKind, Flintlock, Pellets},
+            ItemType,
+        },
+    };
+
+    use super::*;
+
+    fn item(build: impl FnOnce(&mut ItemComponents)) -> ItemComponents {
+        let mut item = ItemComponents::new(ItemType::new("Test"));
+        build(&mut item);
+        item
+    }
+
+    fn sword(damage: i32) -> ItemComponents {
+        item(|item| item.damage = Some(Damage::new(damage)))
+    }
+
+    fn pistol() -> ItemComponents {
+        let mut pistol = sword(4);
+        pistol.flintlock = Some(Flintlock::empty(AmmoKind::Shot, 1));
+        pistol
+    }
+
+    fn pellets() -> ItemComponents {
+        item(|item| {
+            item.pellets = Some(Pellets {
+                load_amount: 1,
+                target: AbilityTarget::with_all_attributes(TargetFilter::Next(1)),
+            })
+        })
+    }
+
+    fn enemy(hp: i32, damage: i32) -> SimEnemy {
+        SimEnemy::new(
+            Hp::new(hp),
+            Damage::new(damage),
+            EnemyBehaviour::Strike,
+            vec![],
+        )
+    }
+
+    fn player() -> SimPlayer {
+        SimPlayer::new(Hp::new(20))
+    }
+
+    fn is_permutation(order: &[usize], len: usize) -> bool {
+        let mut sorted = order.to_vec();
+        sorted.sort_unstable();
+        sorted == (0..len).collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn pellets_are_moved_in_front_of_their_flintlock() {
+        let items = vec![pistol(), pellets()];
+        let enemies = vec![enemy(8, 0)];
+        let as_given = Solution::evaluate(vec![0, 1], &items, &player(), &enemies);
+        let best = solve_arrangement(&items, &player(), &enemies, SolverGoal::FastestKill);
+        assert_eq!(best.order, [1, 0]);
+        assert_eq!(best.outcome, Some(BattleOutcome::Won));
+        assert!(best.turns < as_given.turns);
+    }
+
+    #[test]
+    fn goals_can_pick_different_orders() {
+        let guard = item(|item| item.sea_legs = Some(SeaLegs::new(5)));
+        let items = vec![sword(10), guard];
+        let enemies = vec![enemy(20, 5)];
+
+        let fastest = solve_arrangement(&items, &player(), &enemies, SolverGoal::FastestKill);
+        assert_eq!(fastest.order, [0, 1]);
+        assert_eq!((fastest.turns, fastest.damage_taken), (3, 5));
+
+        let safest = solve_arrangement(&items, &player(), &enemies, SolverGoal::LeastDamage);
+        assert_eq!(safest.order, [1, 0]);
+        assert_eq!((safest.turns, safest.damage_taken), (4, 1));
+    }
+
+    #[test]
+    fn large_scrolls_fall_back_to_a_local_search() {
+        let mut items = vec![pistol(), pellets()];
+        items.extend((0..EXHAUSTIVE_LIMIT).map(|_| sword(1)));
+        assert!(items.len() > EXHAUSTIVE_LIMIT);
+        let enemies = vec![enemy(30, 0)];
+
+        let best = solve_arrangement(&items, &player(), &enemies, SolverGoal::FastestKill);
+        assert!(is_permutation(&best.order, items.len()));
+        assert_eq!(best.outcome, Some(BattleOutcome::Won));
+        let pellets_at = best.order.iter().position(|&i| i == 1).unwrap();
+        assert_eq!(best.order[pellets_at + 1], 0);
+    }
+}
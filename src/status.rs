@@ -0,0 +1,276 @@
+use bevy::{prelude::*, ui::RelativeCursorPosition};
+use serde::Deserialize;
+
+use crate::{
+    assets::{GameSprites, ICON_INDEX_BLEED, ICON_INDEX_BURN, ICON_INDEX_POISON, ICON_INDEX_STUN},
+    common::Name,
+    tooltip::{
+        RefreshTooltips, TooltipComponent, TooltipSection, TooltipSectionIndex, Tooltipable,
+    },
+};
+
+const STATUS_ICON_SIZE: f32 = 8.;
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            update_status_bar_ui.run_if(any_with_component::<StatusBarUI>),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum StatusKind {
+    Poison,
+    Bleed,
+    Stun,
+    Burn,
+}
+
+impl StatusKind {
+    pub fn name(&self) -> String {
+        match self {
+            StatusKind::Poison => "Poison".to_string(),
+            StatusKind::Bleed => "Bleed".to_string(),
+            StatusKind::Stun => "Stun".to_string(),
+            StatusKind::Burn => "Burn".to_string(),
+        }
+    }
+
+    fn icon(&self) -> usize {
+        match self {
+            StatusKind::Poison => ICON_INDEX_POISON,
+            StatusKind::Bleed => ICON_INDEX_BLEED,
+            StatusKind::Stun => ICON_INDEX_STUN,
+            StatusKind::Burn => ICON_INDEX_BURN,
+        }
+    }
+}
+
+pub fn turns(duration: i32) -> String {
+    match duration {
+        1 => "1 turn".to_string(),
+        n => format!("{n} turns"),
+    }
+}
+
+/// An effect over time. `stacks` is how hard it hits and `duration` how many
+/// more turns it lasts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub stacks: i32,
+    pub duration: i32,
+}
+
+impl StatusEffect {
+    fn is_active(&self) -> bool {
+        self.stacks > 0 && self.duration > 0
+    }
+
+    fn description(&self) -> String {
+        let turns = turns(self.duration);
+        match self.kind {
+            StatusKind::Poison => format!("Takes {} damage each turn\nfor {turns}", self.stacks),
+            StatusKind::Bleed => format!("Takes {} damage on acting\nfor {turns}", self.stacks),
+            StatusKind::Stun => format!("Skips the next {turns}"),
+            StatusKind::Burn => format!("Takes {} damage each turn,\nfading by 1", self.stacks),
+        }
+    }
+}
+
+/// Status effects on the player or an enemy. They tick at the start of their
+/// owner's turn, apart from stun which wears off as turns are skipped.
+#[derive(Component, Default, Clone, PartialEq, Debug)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.0.iter().find(|effect| effect.kind == kind)
+    }
+
+    /// Adds `effect`, stacking onto any effect of the same kind and keeping the
+    /// longer duration.
+    pub fn apply(&mut self, effect: StatusEffect) {
+        match self.0.iter_mut().find(|e| e.kind == effect.kind) {
+            Some(existing) => {
+                existing.stacks += effect.stacks;
+                existing.duration = existing.duration.max(effect.duration);
+            }
+            None => self.0.push(effect),
+        }
+        self.0.retain(StatusEffect::is_active);
+    }
+
+    /// Ticks every effect down by a turn and returns the damage they deal.
+    pub fn tick(&mut self) -> i32 {
+        let mut damage = 0;
+        for effect in self.0.iter_mut() {
+            match effect.kind {
+                StatusKind::Poison => damage += effect.stacks,
+                StatusKind::Burn => {
+                    damage += effect.stacks;
+                    effect.stacks -= 1;
+                }
+                StatusKind::Bleed => (),
+                StatusKind::Stun => continue,
+            }
+            effect.duration -= 1;
+        }
+        self.0.retain(StatusEffect::is_active);
+        damage
+    }
+
+    /// Damage taken for acting while bleeding.
+    pub fn bleed(&self) -> i32 {
+        self.get(StatusKind::Bleed)
+            .map_or(0, |effect| effect.stacks)
+    }
+
+    /// Uses up a turn of stun, returning whether the turn is skipped.
+    pub fn skip_turn(&mut self) -> bool {
+        let Some(stun) = self.0.iter_mut().find(|e| e.kind == StatusKind::Stun) else {
+            return false;
+        };
+        stun.duration -= 1;
+        self.0.retain(StatusEffect::is_active);
+        true
+    }
+}
+
+/// Row of icons showing the status effects on `owner`.
+#[derive(Component)]
+pub struct StatusBarUI {
+    owner: Entity,
+}
+
+impl StatusBarUI {
+    pub fn spawn(parent: &mut ChildBuilder, owner: Entity, tag: impl Component) {
+        parent.spawn((
+            tag,
+            StatusBarUI { owner },
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(1.),
+                    min_height: Val::Px(STATUS_ICON_SIZE),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+}
+
+#[derive(Component)]
+pub struct StatusIcon(StatusEffect);
+
+impl TooltipComponent for StatusIcon {
+    fn get_tooltip_section(&self) -> TooltipSection {
+        TooltipSection::default_color(self.0.description(), TooltipSectionIndex::Body)
+    }
+}
+
+fn update_status_bar_ui(
+    mut commands: Commands,
+    mut refresh_tooltips_ew: EventWriter<RefreshTooltips>,
+    game_sprites: Res<GameSprites>,
+    status_bar_ui_q: Query<(Entity, &StatusBarUI)>,
+    status_effects_q: Query<&StatusEffects, Changed<StatusEffects>>,
+) {
+    for (status_bar_ui, StatusBarUI { owner }) in status_bar_ui_q.iter() {
+        let Ok(status_effects) = status_effects_q.get(*owner) else {
+            continue;
+        };
+        commands
+            .entity(status_bar_ui)
+            .despawn_descendants()
+            .with_children(|parent| {
+                for effect in status_effects.0.iter() {
+                    parent.spawn((
+                        StatusIcon(*effect),
+                        Name(format!("{} {}", effect.kind.name(), effect.stacks)),
+                        AtlasImageBundle {
+                            image: UiImage::new(game_sprites.items_tile_sheet.clone()),
+                            texture_atlas: TextureAtlas {
+                                layout: game_sprites.items_tile_layout.clone(),
+                                index: effect.kind.icon(),
+                            },
+                            style: Style {
+                                width: Val::Px(STATUS_ICON_SIZE),
+                                height: Val::Px(STATUS_ICON_SIZE),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        RelativeCursorPosition::default(),
+                        Tooltipable::default(),
+                    ));
+                }
+            });
+        // The icon being hovered may have just been despawned.
+        refresh_tooltips_ew.send(RefreshTooltips);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effect(kind: StatusKind, stacks: i32, duration: i32) -> StatusEffect {
+        StatusEffect {
+            kind,
+            stacks,
+            duration,
+        }
+    }
+
+    #[test]
+    fn applying_stacks_and_keeps_the_longer_duration() {
+        let mut statuses = StatusEffects::default();
+        statuses.apply(effect(StatusKind::Poison, 2, 3));
+        statuses.apply(effect(StatusKind::Poison, 1, 1));
+        statuses.apply(effect(StatusKind::Bleed, 0, 5));
+        assert_eq!(statuses.0, [effect(StatusKind::Poison, 3, 3)]);
+    }
+
+    #[test]
+    fn poison_ticks_until_its_duration_runs_out() {
+        let mut statuses = StatusEffects::default();
+        statuses.apply(effect(StatusKind::Poison, 2, 2));
+        assert_eq!(statuses.tick(), 2);
+        assert_eq!(statuses.tick(), 2);
+        assert_eq!(statuses.tick(), 0);
+        assert!(statuses.0.is_empty());
+    }
+
+    #[test]
+    fn burn_fades_by_one_each_tick() {
+        let mut statuses = StatusEffects::default();
+        statuses.apply(effect(StatusKind::Burn, 3, 3));
+        let damage: Vec<i32> = (0..4).map(|_| statuses.tick()).collect();
+        assert_eq!(damage, [3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn bleed_hurts_on_acting_and_not_on_ticking() {
+        let mut statuses = StatusEffects::default();
+        statuses.apply(effect(StatusKind::Bleed, 2, 1));
+        assert_eq!(statuses.bleed(), 2);
+        assert_eq!(statuses.tick(), 0);
+        assert_eq!(statuses.bleed(), 0);
+    }
+
+    #[test]
+    fn stun_skips_turns_without_ticking() {
+        let mut statuses = StatusEffects::default();
+        statuses.apply(effect(StatusKind::Stun, 1, 2));
+        assert_eq!(statuses.tick(), 0);
+        assert!(statuses.skip_turn());
+        assert!(statuses.skip_turn());
+        assert!(!statuses.skip_turn());
+    }
+}
@@ -3,11 +3,16 @@ use bevy::{prelude::*, ui::RelativeCursorPosition, window::PrimaryWindow};
 use crate::{
     assets::{GameFonts, GameMaterials},
     common::Name,
+    enemy::EnemyAffixes,
     items::{
-        abilities::{Cursed, Damage, Hearties, Heave, Jolly, SeaLegs, Swashbuckle, Vitality},
-        attributes::{Cannonball, Flintlock, Pellets, Pointy},
+        abilities::{
+            Bleed, Buff, Burn, Cursed, Damage, Hearties, Heave, Jolly, ModifierScope, Poison,
+            SeaLegs, Stun, Swashbuckle, Vitality,
+        },
+        attributes::{Cannonball, Flintlock, Pellets, Pointy, Spread},
         Consumable, Rarity,
     },
+    status::StatusIcon,
     AppState,
 };
 
@@ -33,19 +38,33 @@ impl Plugin for TooltipPlugin {
         app.register_component_as::<dyn TooltipComponent, Swashbuckle>();
         app.register_component_as::<dyn TooltipComponent, Jolly>();
         app.register_component_as::<dyn TooltipComponent, Vitality>();
+        app.register_component_as::<dyn TooltipComponent, Poison>();
+        app.register_component_as::<dyn TooltipComponent, Bleed>();
+        app.register_component_as::<dyn TooltipComponent, Burn>();
+        app.register_component_as::<dyn TooltipComponent, Stun>();
+        app.register_component_as::<dyn TooltipComponent, Buff>();
 
         app.register_component_as::<dyn TooltipComponent, Pointy>();
         app.register_component_as::<dyn TooltipComponent, Flintlock>();
         app.register_component_as::<dyn TooltipComponent, Pellets>();
         app.register_component_as::<dyn TooltipComponent, Cannonball>();
+        app.register_component_as::<dyn TooltipComponent, Spread>();
 
         app.register_component_as::<dyn TooltipComponent, Name>();
         app.register_component_as::<dyn TooltipComponent, Rarity>();
         app.register_component_as::<dyn TooltipComponent, Consumable>();
+        app.register_component_as::<dyn TooltipComponent, ModifierScope>();
+        app.register_component_as::<dyn TooltipComponent, StatusIcon>();
+        app.register_component_as::<dyn TooltipComponent, EnemyAffixes>();
 
-        app.add_systems(
+        app.add_event::<RefreshTooltips>().add_systems(
             Update,
-            (spawn_tooltips, update_tooltip_positions, destroy_tooltips)
+            (
+                refresh_tooltips,
+                spawn_tooltips,
+                update_tooltip_positions,
+                destroy_tooltips,
+            )
                 .chain()
                 .run_if(not(in_state(AppState::LoadingAssets))),
         );
@@ -57,6 +76,10 @@ pub trait TooltipComponent {
     fn get_tooltip_section(&self) -> TooltipSection;
 }
 
+/// Rebuilds any open tooltip from its entity's current components.
+#[derive(Event)]
+pub struct RefreshTooltips;
+
 #[derive(Component, Default, Debug)]
 pub enum Tooltipable {
     #[default]
@@ -143,7 +166,6 @@ impl Tooltip {
                                 color: text_section.color,
                                 font_size: text_section.index.font_size(),
                                 font: game_fonts.font.clone(),
-                                ..default()
                             },
                         ),
                         ..default()
@@ -183,9 +205,26 @@ fn spawn_tooltips(
         tooltip_sections.sort_by(|a, b| a.index.cmp(&b.index));
         let tooltip = Tooltip(tooltip_sections);
         tooltip.spawn(&mut commands, &game_materials, &game_fonts);
-        commands.get_entity(entity).map(|mut ec| {
+        if let Some(mut ec) = commands.get_entity(entity) {
             ec.insert(tooltip);
-        });
+        }
+    }
+}
+
+fn refresh_tooltips(
+    mut commands: Commands,
+    mut refresh_tooltips_er: EventReader<RefreshTooltips>,
+    tooltipped_q: Query<Entity, With<Tooltip>>,
+    tooltip_q: Query<Entity, With<TooltipRoot>>,
+) {
+    if refresh_tooltips_er.read().last().is_none() {
+        return;
+    }
+    for entity in tooltipped_q.iter() {
+        commands.entity(entity).remove::<Tooltip>();
+    }
+    for tooltip in tooltip_q.iter() {
+        commands.entity(tooltip).despawn_recursive();
     }
 }
 
@@ -215,9 +254,9 @@ fn destroy_tooltips(
         } {
             commands.entity(entity).remove::<Tooltip>();
             for tooltip in tooltip_q.iter() {
-                commands.get_entity(tooltip).map(|ec| {
+                if let Some(ec) = commands.get_entity(tooltip) {
                     ec.despawn_recursive();
-                });
+                }
             }
         }
     }
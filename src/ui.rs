@@ -3,6 +3,7 @@ use bevy::prelude::*;
 use crate::{
     assets::{GameFonts, GameSprites},
     common::Hp,
+    rng::{start_run, Rng},
     AppState,
 };
 
@@ -13,6 +14,10 @@ impl Plugin for UIPlugin {
         app.add_systems(OnEnter(AppState::InitGame), setup_root_node)
             .add_systems(OnEnter(AppState::GameStart), spawn_start_game_button)
             .add_systems(OnExit(AppState::GameStart), destroy_start_button)
+            .add_systems(OnEnter(AppState::GameStart), spawn_seed_ui.after(start_run))
+            .add_systems(OnEnter(AppState::GameOver), spawn_seed_ui)
+            .add_systems(OnExit(AppState::GameStart), destroy_seed_ui)
+            .add_systems(OnExit(AppState::GameOver), destroy_seed_ui)
             .add_systems(
                 Update,
                 start_button_system.run_if(any_with_component::<StartGameButton>),
@@ -62,6 +67,9 @@ pub struct StartGameButton;
 #[derive(Component)]
 pub struct RestartGameButton;
 
+#[derive(Component)]
+pub struct SeedUI;
+
 impl HealthBarUI {
     pub fn spawn(
         parent: &mut ChildBuilder,
@@ -386,3 +394,37 @@ fn restart_button_system(
         Interaction::None => image.texture = game_sprites.restart_game_button.clone(),
     };
 }
+
+fn spawn_seed_ui(
+    mut commands: Commands,
+    game_fonts: Res<GameFonts>,
+    rng: Res<Rng>,
+    bottom_center_ui_q: Query<Entity, With<BottomCenterUI>>,
+) {
+    let seed_text = commands
+        .spawn((
+            SeedUI,
+            TextBundle {
+                text: Text::from_section(
+                    format!("Seed: {}", rng.seed()),
+                    TextStyle {
+                        color: FONT_COLOR,
+                        font_size: FONT_SIZE,
+                        font: game_fonts.font.clone(),
+                    },
+                ),
+                ..default()
+            },
+        ))
+        .id();
+
+    commands
+        .entity(bottom_center_ui_q.single())
+        .insert_children(0, &[seed_text]);
+}
+
+fn destroy_seed_ui(mut commands: Commands, seed_ui_q: Query<Entity, With<SeedUI>>) {
+    for seed_ui in seed_ui_q.iter() {
+        commands.entity(seed_ui).despawn_recursive();
+    }
+}
@@ -0,0 +1,306 @@
+mod common;
+
+use bevy::{
+    ecs::event::Events,
+    input::keyboard::Key,
+    prelude::{KeyCode, Text, With},
+};
+use captains_gamble::{
+    battle::{sim::EffectStat, ActiveBattle, BattleEffect, BattleEvent},
+    common::Hp,
+    enemy::{EnemyAffix, EnemyArmorUI, EnemyDefinitions, NextEncounter},
+    inventory::LootBonus,
+    items::{abilities::AbilityKind, ItemType, Rarity},
+    log::{BattleLog, LogCategory},
+    player::STARTING_PLAYER_HP,
+    replay::{Replay, RunRecorder},
+    rng::Rng,
+    save::{ContinueRun, SaveGame},
+    AppState, BattleWins,
+};
+use common::TestApp;
+
+const SEED: u64 = 7;
+
+#[test]
+fn start_game_offers_starting_loot() {
+    let mut app = TestApp::new(SEED);
+    app.click_button("Start Game");
+    app.update();
+
+    assert_eq!(app.state(), AppState::OrganizeInventory);
+    let loot_scroll = app.loot_scroll();
+    let inventory_scroll = app.inventory_scroll();
+    assert_eq!(
+        app.item_types(loot_scroll),
+        vec![ItemType::new("WoodenSword"), ItemType::new("Grog")]
+    );
+    assert!(app.items(inventory_scroll).is_empty());
+}
+
+#[test]
+fn dragging_moves_items_between_scrolls() {
+    let mut app = TestApp::new(SEED);
+    app.click_button("Start Game");
+    app.update();
+    let loot_scroll = app.loot_scroll();
+    let inventory_scroll = app.inventory_scroll();
+
+    let grog = app.items(loot_scroll)[1];
+    app.drag_item(grog, inventory_scroll, 0);
+    assert_eq!(
+        app.item_types(loot_scroll),
+        vec![ItemType::new("WoodenSword")]
+    );
+    assert_eq!(
+        app.item_types(inventory_scroll),
+        vec![ItemType::new("Grog")]
+    );
+
+    let sword = app.items(loot_scroll)[0];
+    app.drag_item(sword, inventory_scroll, 0);
+    assert_eq!(
+        app.item_types(inventory_scroll),
+        vec![ItemType::new("WoodenSword"), ItemType::new("Grog")]
+    );
+
+    app.drag_item(grog, loot_scroll, 0);
+    assert_eq!(app.item_types(loot_scroll), vec![ItemType::new("Grog")]);
+    assert_eq!(
+        app.item_types(inventory_scroll),
+        vec![ItemType::new("WoodenSword")]
+    );
+}
+
+#[test]
+fn suggest_reorders_the_inventory_scroll() {
+    let mut app = TestApp::new(SEED);
+    app.click_button("Start Game");
+    app.update();
+    let loot_scroll = app.loot_scroll();
+    let inventory_scroll = app.inventory_scroll();
+    let (sword, grog) = (app.items(loot_scroll)[0], app.items(loot_scroll)[1]);
+    app.drag_item(grog, inventory_scroll, 0);
+    app.drag_item(sword, inventory_scroll, 1);
+    assert_eq!(app.items(inventory_scroll), vec![grog, sword]);
+
+    app.click_button("Fastest Kill");
+    app.update_until(|app| app.items(inventory_scroll) == vec![sword, grog]);
+}
+
+#[test]
+fn starting_loot_wins_the_first_battle() {
+    let mut app = TestApp::new(SEED);
+    app.click_button("Start Game");
+    app.update();
+    app.take_all_loot();
+
+    app.click_button("Start Battle");
+    app.update();
+    assert_eq!(app.state(), AppState::Battling);
+    app.fight();
+
+    assert_eq!(app.state(), AppState::OrganizeInventory);
+    assert_eq!(app.resource::<BattleWins>().0, 1);
+    let inventory_scroll = app.inventory_scroll();
+    assert!(app
+        .item_types(inventory_scroll)
+        .contains(&ItemType::new("WoodenSword")));
+}
+
+#[test]
+fn replaying_a_recorded_run_reaches_the_same_state() {
+    let mut app = TestApp::new(SEED);
+    app.click_button("Start Game");
+    app.update();
+    app.take_all_loot();
+    app.click_button("Start Battle");
+    app.update();
+    app.fight();
+    assert_eq!(app.state(), AppState::OrganizeInventory);
+    let recording = app.resource::<RunRecorder>().recording().clone();
+    let (battle_wins, hp) = (app.resource::<BattleWins>().0, app.player_hp());
+    assert_eq!(battle_wins, 1);
+
+    let mut replay = TestApp::new(SEED);
+    replay.app.insert_resource(Replay::new(recording));
+    replay.update_until(|app| {
+        app.state() == AppState::OrganizeInventory && app.resource::<Replay>().is_finished()
+    });
+    assert_eq!(replay.resource::<BattleWins>().0, battle_wins);
+    assert_eq!(replay.player_hp(), hp);
+}
+
+#[test]
+fn continued_runs_keep_the_loot_owed_for_the_last_battle() {
+    let mut app = TestApp::new(SEED);
+    app.click_button("Start Game");
+    app.update();
+    app.take_all_loot();
+    let mut skeleton = app
+        .resource::<EnemyDefinitions>()
+        .get("SkeletonPirate")
+        .unwrap()
+        .clone();
+    skeleton.loot = Some(Rarity::Mythic);
+    app.app.world.insert_resource(NextEncounter(vec![skeleton]));
+    app.click_button("Start Battle");
+    app.update();
+    app.fight();
+    let bonus = *app.resource::<LootBonus>();
+    assert_eq!(bonus.guaranteed, Some(Rarity::Mythic));
+
+    let continue_with = |loot_bonus| {
+        let mut app = TestApp::new(SEED);
+        let save = SaveGame {
+            battle_wins: 1,
+            hp: Hp::new(STARTING_PLAYER_HP),
+            items: vec![],
+            rng: Rng::from_seed(SEED),
+            loot_bonus,
+        };
+        app.app.world.insert_resource(ContinueRun(save));
+        app.set_state(AppState::OrganizeInventory);
+        assert_eq!(*app.resource::<LootBonus>(), loot_bonus);
+        let loot_scroll = app.loot_scroll();
+        app.item_types(loot_scroll)
+    };
+    let plain = continue_with(LootBonus::default());
+    let owed = continue_with(bonus);
+    assert_eq!(owed.len(), plain.len() + 1);
+    assert_eq!(owed[..plain.len()], plain);
+}
+
+#[test]
+fn empty_inventory_loses_and_restarts() {
+    let mut app = TestApp::new(SEED);
+    app.click_button("Start Game");
+    app.update();
+
+    app.click_button("Start Battle");
+    app.update();
+    app.fight();
+
+    assert_eq!(app.state(), AppState::GameOver);
+    assert!(app.player_hp().is_dead());
+
+    app.click_button("Restart");
+    app.update();
+    assert_eq!(app.state(), AppState::GameStart);
+    assert_eq!(app.resource::<BattleWins>().0, 0);
+    assert_eq!(app.player_hp(), Hp::new(STARTING_PLAYER_HP));
+}
+
+#[test]
+fn armor_panel_shows_the_armor_the_sim_applies() {
+    let mut app = TestApp::new(SEED);
+    app.click_button("Start Game");
+    app.update();
+    app.take_all_loot();
+    let mut skeleton = app
+        .resource::<EnemyDefinitions>()
+        .get("SkeletonPirate")
+        .unwrap()
+        .clone();
+    skeleton.armor = 1;
+    skeleton.affixes = vec![EnemyAffix::Armored(2)];
+    app.app.world.insert_resource(NextEncounter(vec![skeleton]));
+
+    app.click_button("Start Battle");
+    app.update();
+    assert_eq!(app.resource::<ActiveBattle>().sim.enemies[0].stats.armor, 3);
+    let armor = app
+        .app
+        .world
+        .query_filtered::<&Text, With<EnemyArmorUI>>()
+        .single(&app.app.world)
+        .sections[1]
+        .value
+        .clone();
+    assert_eq!(armor, "3");
+}
+
+#[test]
+fn tab_cycles_target_between_enemies() {
+    let mut app = TestApp::new(SEED);
+    app.click_button("Start Game");
+    app.update();
+    app.take_all_loot();
+    let skeleton = app
+        .resource::<EnemyDefinitions>()
+        .get("SkeletonPirate")
+        .unwrap()
+        .clone();
+    app.app
+        .world
+        .insert_resource(NextEncounter(vec![skeleton; 3]));
+
+    app.click_button("Start Battle");
+    app.update();
+    let sim = &app.resource::<ActiveBattle>().sim;
+    assert_eq!(sim.enemies.len(), 3);
+    assert_eq!(sim.target, 0);
+
+    app.press_key(KeyCode::Tab, Key::Tab);
+    app.update();
+    assert_eq!(app.resource::<ActiveBattle>().sim.target, 1);
+}
+
+#[test]
+fn battle_events_name_the_item_and_target() {
+    let mut app = TestApp::new(SEED);
+    app.click_button("Start Game");
+    app.update();
+    app.take_all_loot();
+    let inventory_scroll = app.inventory_scroll();
+    let sword = app.items(inventory_scroll)[0];
+
+    app.click_button("Start Battle");
+    app.update();
+    app.press_space();
+
+    let events = app.resource::<Events<BattleEvent>>();
+    let effects: Vec<BattleEffect> = events
+        .get_reader()
+        .read(events)
+        .filter_map(|event| match event {
+            BattleEvent::Effect(effect) => Some(*effect),
+            _ => None,
+        })
+        .collect();
+    let hit = effects
+        .iter()
+        .find(|effect| effect.ability == Some(AbilityKind::Damage))
+        .expect("the sword's hit is reported");
+    assert_eq!(hit.item, Some(sword));
+    assert_eq!(hit.stat, EffectStat::Hp);
+    assert!(hit.after < hit.before);
+    assert!(app.enemies().contains(&hit.target));
+}
+
+#[test]
+fn battle_log_is_shown_again_on_game_over() {
+    let mut app = TestApp::new(SEED);
+    app.click_button("Start Game");
+    app.update();
+
+    app.click_button("Start Battle");
+    app.update();
+    app.fight();
+    app.update();
+    assert_eq!(app.state(), AppState::GameOver);
+
+    let log = app.resource::<BattleLog>();
+    let enemy_lines = log
+        .entries
+        .iter()
+        .filter(|entry| entry.category == Some(LogCategory::Enemy))
+        .count();
+    assert!(enemy_lines > 0);
+    let lines = log.entries.len();
+    assert_eq!(app.log_messages(), lines);
+
+    app.click_button("Enemy");
+    app.update();
+    assert_eq!(app.log_messages(), lines - enemy_lines);
+}
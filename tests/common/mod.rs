@@ -0,0 +1,290 @@
+//! Drives the game headlessly: `MinimalPlugins` plus `GamePlugin`, with
+//! default handles standing in for sprites, fonts and audio, and the real
+//! item and enemy definitions.
+
+use std::{sync::Once, time::Duration};
+
+use bevy::{
+    asset::AssetPlugin,
+    input::{
+        keyboard::{Key, KeyboardInput},
+        mouse::MouseButtonInput,
+        ButtonState, InputPlugin,
+    },
+    prelude::*,
+    time::TimeUpdateStrategy,
+    ui::RelativeCursorPosition,
+    window::PrimaryWindow,
+};
+use captains_gamble::{
+    assets::{load_ron, GameAudio, GameData, GameFonts, GameMaterials, GameSprites},
+    common::Hp,
+    enemy::{Enemy, EnemyDefinitions},
+    inventory::{InventoryScrollUI, LootScrollUI, INVENTORY_SCROLL_SIZE, LOOT_SCROLL_SIZE},
+    items::{definitions::ItemDefinitions, ItemType},
+    log::LogMessage,
+    player::Player,
+    rng::RequestedSeed,
+    AppState, GamePlugin,
+};
+
+pub const FRAME_TIME: Duration = Duration::from_millis(100);
+pub const MAX_FRAMES: usize = 1000;
+
+const ITEMS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/data/base.items.ron");
+const ENEMIES_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/data/base.enemies.ron");
+
+static WORK_DIR: Once = Once::new();
+
+/// Saves and replays are written relative to the working directory, so keep
+/// them out of the checkout.
+fn use_scratch_work_dir() {
+    WORK_DIR.call_once(|| {
+        let dir = std::env::temp_dir().join("captains_gamble_tests");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::env::set_current_dir(dir).unwrap();
+    });
+}
+
+pub struct TestApp {
+    pub app: App,
+    window: Entity,
+}
+
+impl TestApp {
+    /// Builds the app and steps it into `GameStart` with a run seeded by
+    /// `seed`.
+    pub fn new(seed: u64) -> Self {
+        use_scratch_work_dir();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(AssetPlugin {
+                watch_for_changes_override: Some(false),
+                ..default()
+            })
+            .add_plugins(InputPlugin)
+            .init_asset::<Image>()
+            .init_asset::<TextureAtlasLayout>()
+            .add_plugins(GamePlugin)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+            .insert_resource(RequestedSeed(Some(seed)))
+            .insert_resource(GameSprites::default())
+            .insert_resource(GameFonts::default())
+            .insert_resource(GameAudio::default())
+            .insert_resource(GameMaterials::default());
+
+        let item_definitions: ItemDefinitions = load_ron(ITEMS_PATH).unwrap();
+        let enemy_definitions: EnemyDefinitions = load_ron(ENEMIES_PATH).unwrap();
+        let game_data = GameData {
+            items: app
+                .world
+                .resource_mut::<Assets<ItemDefinitions>>()
+                .add(item_definitions),
+            enemies: app
+                .world
+                .resource_mut::<Assets<EnemyDefinitions>>()
+                .add(enemy_definitions),
+        };
+        app.insert_resource(game_data);
+        let window = app.world.spawn((Window::default(), PrimaryWindow)).id();
+
+        let mut test_app = Self { app, window };
+        test_app.set_state(AppState::InitGame);
+        test_app.update_until(|app| app.state() == AppState::GameStart);
+        test_app
+    }
+
+    pub fn update(&mut self) {
+        self.app.update();
+    }
+
+    /// Steps frames until `done` holds, panicking after `MAX_FRAMES`.
+    pub fn update_until(&mut self, done: impl Fn(&mut Self) -> bool) {
+        for _ in 0..MAX_FRAMES {
+            if done(self) {
+                return;
+            }
+            self.update();
+        }
+        panic!("Gave up after {MAX_FRAMES} frames in {:?}", self.state());
+    }
+
+    pub fn state(&self) -> AppState {
+        self.app.world.resource::<State<AppState>>().get().clone()
+    }
+
+    pub fn set_state(&mut self, state: AppState) {
+        self.app
+            .world
+            .resource_mut::<NextState<AppState>>()
+            .set(state);
+        self.update();
+    }
+
+    pub fn resource<R: Resource>(&self) -> &R {
+        self.app.world.resource::<R>()
+    }
+
+    pub fn player_hp(&mut self) -> Hp {
+        self.app
+            .world
+            .query_filtered::<&Hp, With<Player>>()
+            .single(&self.app.world)
+            .clone()
+    }
+
+    pub fn enemies(&mut self) -> Vec<Entity> {
+        self.app
+            .world
+            .query_filtered::<Entity, With<Enemy>>()
+            .iter(&self.app.world)
+            .collect()
+    }
+
+    /// Counts the lines shown in the battle log.
+    pub fn log_messages(&mut self) -> usize {
+        self.app
+            .world
+            .query_filtered::<(), With<LogMessage>>()
+            .iter(&self.app.world)
+            .count()
+    }
+
+    /// Presses and releases a key over two frames.
+    pub fn press_key(&mut self, key_code: KeyCode, logical_key: Key) {
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            self.app.world.send_event(KeyboardInput {
+                key_code,
+                logical_key: logical_key.clone(),
+                state,
+                window: self.window,
+            });
+            self.update();
+        }
+    }
+
+    pub fn press_space(&mut self) {
+        self.press_key(KeyCode::Space, Key::Space);
+    }
+
+    /// Clicks the button labelled `label`.
+    pub fn click_button(&mut self, label: &str) {
+        let button = self
+            .app
+            .world
+            .query_filtered::<(Entity, &Children), With<Button>>()
+            .iter(&self.app.world)
+            .find(|(_, children)| {
+                children.iter().any(|&child| {
+                    self.app
+                        .world
+                        .get::<Text>(child)
+                        .is_some_and(|text| text.sections[0].value == label)
+                })
+            })
+            .map(|(button, _)| button)
+            .unwrap_or_else(|| panic!("No {label} button in {:?}", self.state()));
+
+        *self.app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Pressed;
+        self.update();
+        if let Some(mut interaction) = self.app.world.get_mut::<Interaction>(button) {
+            *interaction = Interaction::None;
+        }
+    }
+
+    pub fn loot_scroll(&mut self) -> Entity {
+        self.scroll::<LootScrollUI>()
+    }
+
+    pub fn inventory_scroll(&mut self) -> Entity {
+        self.scroll::<InventoryScrollUI>()
+    }
+
+    fn scroll<C: Component>(&mut self) -> Entity {
+        self.app
+            .world
+            .query_filtered::<Entity, With<C>>()
+            .single(&self.app.world)
+    }
+
+    /// The items on `scroll`, in order.
+    pub fn items(&self, scroll: Entity) -> Vec<Entity> {
+        self.app
+            .world
+            .get::<Children>(scroll)
+            .map(|children| {
+                children
+                    .iter()
+                    .filter(|&&child| self.app.world.get::<ItemType>(child).is_some())
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn item_types(&self, scroll: Entity) -> Vec<ItemType> {
+        self.items(scroll)
+            .into_iter()
+            .map(|item| self.app.world.get::<ItemType>(item).unwrap().clone())
+            .collect()
+    }
+
+    /// Drags `item` onto slot `index` of `scroll`, the way the mouse would:
+    /// press over the item, then release over the slot.
+    pub fn drag_item(&mut self, item: Entity, scroll: Entity, index: usize) {
+        self.hover(item, Some(Vec2::splat(0.5)));
+        self.send_mouse(ButtonState::Pressed);
+        self.hover(item, None);
+
+        let size = if scroll == self.inventory_scroll() {
+            INVENTORY_SCROLL_SIZE
+        } else {
+            LOOT_SCROLL_SIZE
+        };
+        let x = (index as f32 + 0.5) / size as f32;
+        self.hover(scroll, Some(Vec2::new(x, 0.5)));
+        self.send_mouse(ButtonState::Released);
+        self.hover(scroll, None);
+    }
+
+    /// Moves every loot item onto the end of the inventory scroll.
+    pub fn take_all_loot(&mut self) {
+        let (loot_scroll, inventory_scroll) = (self.loot_scroll(), self.inventory_scroll());
+        for item in self.items(loot_scroll) {
+            let index = self.items(inventory_scroll).len();
+            self.drag_item(item, inventory_scroll, index);
+        }
+    }
+
+    fn hover(&mut self, entity: Entity, position: Option<Vec2>) {
+        *self
+            .app
+            .world
+            .get_mut::<RelativeCursorPosition>(entity)
+            .unwrap() = RelativeCursorPosition {
+            normalized_visible_node_rect: Rect::new(0., 0., 1., 1.),
+            normalized: position,
+        };
+    }
+
+    fn send_mouse(&mut self, state: ButtonState) {
+        self.app.world.send_event(MouseButtonInput {
+            button: MouseButton::Left,
+            state,
+            window: self.window,
+        });
+        self.update();
+    }
+
+    /// Presses space on every player turn until the battle is over.
+    pub fn fight(&mut self) {
+        self.update_until(|app| {
+            if app.state() != AppState::Battling {
+                return true;
+            }
+            app.press_space();
+            false
+        });
+    }
+}